@@ -2,7 +2,7 @@
 
 use axum::{
     extract::FromRequestParts,
-    http::{header::AUTHORIZATION, request::Parts},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
 };
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
@@ -52,6 +52,20 @@ impl JwtClaims {
     pub fn can_download(&self) -> bool {
         self.has_scope("download") || self.has_scope("*")
     }
+
+    /// 檢查是否可使用高優先權
+    ///
+    /// 必須明確授予 `priority:high`（或 `*`），空 scope 不視為授權。
+    pub fn can_use_high_priority(&self) -> bool {
+        self.scope.iter().any(|s| s == "priority:high" || s == "*")
+    }
+
+    /// 檢查是否有管理權限
+    ///
+    /// 必須明確授予 `admin`（或 `*`），空 scope 不視為授權。
+    pub fn is_admin(&self) -> bool {
+        self.scope.iter().any(|s| s == "admin" || s == "*")
+    }
 }
 
 /// 已認證的使用者
@@ -107,17 +121,30 @@ pub struct AppState {
     pub jwt_validator: Arc<JwtValidator>,
     pub engine_registry: crate::engine::EngineRegistry,
     pub job_store: crate::job::JobStore,
+    pub job_queue: crate::queue::JobQueue,
+    pub upload_sessions: crate::resumable::UploadSessionStore,
+    pub retention_stats: crate::janitor::RetentionStats,
     pub graphql_schema: Option<Arc<crate::graphql::ApiSchema>>,
 }
 
 impl AppState {
     pub fn new(config: AppConfig) -> Self {
         let jwt_validator = JwtValidator::new(&config.jwt_secret);
+        let job_store =
+            crate::job::JobStore::open(&config.database_path).expect("Failed to open job database");
+        let job_queue = crate::queue::JobQueue::new(config.queue_config());
+        let upload_sessions =
+            crate::resumable::UploadSessionStore::with_database(job_store.database().clone());
+        let engine_registry = crate::catalog::load_engine_registry(&config)
+            .unwrap_or_else(|e| panic!("Failed to load engine catalog: {}", e));
         Self {
             config,
             jwt_validator: Arc::new(jwt_validator),
-            engine_registry: crate::engine::EngineRegistry::new(),
-            job_store: crate::job::JobStore::new(),
+            engine_registry,
+            job_store,
+            job_queue,
+            upload_sessions,
+            retention_stats: Default::default(),
             graphql_schema: None,
         }
     }
@@ -139,6 +166,16 @@ impl AuthenticatedUser {
     pub fn can_download(&self) -> bool {
         self.claims.can_download()
     }
+
+    /// 檢查是否可使用高優先權
+    pub fn can_use_high_priority(&self) -> bool {
+        self.claims.can_use_high_priority()
+    }
+
+    /// 檢查是否有管理權限
+    pub fn is_admin(&self) -> bool {
+        self.claims.is_admin()
+    }
 }
 
 /// 從請求中提取已認證使用者
@@ -146,9 +183,15 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
     type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        AuthenticatedUser::from_headers(&parts.headers, state)
+    }
+}
+
+impl AuthenticatedUser {
+    /// 從請求標頭驗證 Bearer Token
+    pub fn from_headers(headers: &HeaderMap, state: &AppState) -> Result<Self, ApiError> {
         // 取得 Authorization header
-        let auth_header = parts
-            .headers
+        let auth_header = headers
             .get(AUTHORIZATION)
             .and_then(|h| h.to_str().ok())
             .ok_or(ApiError::MissingAuthHeader)?;
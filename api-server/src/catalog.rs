@@ -0,0 +1,465 @@
+//! 引擎目錄模組
+//!
+//! 從設定檔載入引擎清單（TOML 或 JSON），取代手動維護 `default_engines`：
+//! - 未設定 `ENGINE_CONFIG_PATH` 或檔案不存在時使用內建引擎
+//! - 格式名稱統一轉為小寫並去除開頭的 `.`
+//! - 設定檔格式或內容有誤時回報所有錯誤，讓伺服器在啟動時失敗
+//! - 執行中可透過檔案變更或 SIGHUP 重新載入；新設定無效時保留原本的清單
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::AppConfig;
+use crate::engine::{Engine, EngineRegistry};
+
+/// 引擎目錄載入錯誤
+#[derive(Error, Debug)]
+pub enum CatalogError {
+    #[error("無法讀取引擎設定檔 {path}：{source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("引擎設定檔格式錯誤：{0}")]
+    Parse(String),
+
+    #[error("引擎設定檔驗證失敗：\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+/// 設定檔格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    Toml,
+    Json,
+}
+
+impl CatalogFormat {
+    /// 依副檔名判斷格式（`.toml` 以外皆視為 JSON）
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// 設定檔根結構
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    engines: Vec<Engine>,
+}
+
+/// 依設定建立引擎註冊表
+pub fn load_engine_registry(config: &AppConfig) -> Result<EngineRegistry, CatalogError> {
+    let Some(path) = config.engine_config_path.as_deref() else {
+        return Ok(EngineRegistry::new());
+    };
+
+    let path = Path::new(path);
+    if !path.exists() {
+        warn!(
+            "⚠️ Engine catalog {} not found, using built-in engines",
+            path.display()
+        );
+        return Ok(EngineRegistry::new());
+    }
+
+    let engines = load_catalog(path)?;
+    info!(
+        "🔧 Loaded {} engines from {}",
+        engines.len(),
+        path.display()
+    );
+    Ok(EngineRegistry::with_engines(engines))
+}
+
+/// 讀取並驗證引擎設定檔
+pub fn load_catalog(path: &Path) -> Result<Vec<Engine>, CatalogError> {
+    let content = std::fs::read_to_string(path).map_err(|source| CatalogError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+
+    parse_catalog(&content, CatalogFormat::from_path(path)).map_err(|e| match e {
+        CatalogError::Parse(message) => {
+            CatalogError::Parse(format!("{}：{}", path.display(), message))
+        }
+        other => other,
+    })
+}
+
+/// 解析並驗證引擎設定內容
+pub fn parse_catalog(content: &str, format: CatalogFormat) -> Result<Vec<Engine>, CatalogError> {
+    let file: CatalogFile = match format {
+        CatalogFormat::Toml => {
+            toml::from_str(content).map_err(|e| CatalogError::Parse(e.to_string()))?
+        }
+        CatalogFormat::Json => {
+            serde_json::from_str(content).map_err(|e| CatalogError::Parse(e.to_string()))?
+        }
+    };
+
+    let engines: Vec<Engine> = file.engines.into_iter().map(normalize_engine).collect();
+    validate_engines(&engines)?;
+    Ok(engines)
+}
+
+/// 驗證引擎清單，回報所有發現的問題
+pub fn validate_engines(engines: &[Engine]) -> Result<(), CatalogError> {
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+
+    if engines.is_empty() {
+        errors.push("至少需要一個引擎".to_string());
+    }
+
+    for (index, engine) in engines.iter().enumerate() {
+        let id = &engine.engine_id;
+        let label = if id.is_empty() {
+            format!("engines[{}]", index)
+        } else {
+            format!("engines[{}] ({})", index, id)
+        };
+
+        if id.is_empty() {
+            errors.push(format!("{}：engine_id 不可為空", label));
+        } else if !is_valid_engine_id(id) {
+            errors.push(format!("{}：engine_id 只能包含小寫英數字、- 與 _", label));
+        } else if !seen.insert(id.clone()) {
+            errors.push(format!("{}：engine_id 重複", label));
+        }
+
+        if engine.engine_name.trim().is_empty() {
+            errors.push(format!("{}：engine_name 不可為空", label));
+        }
+        if engine.input_formats.is_empty() {
+            errors.push(format!("{}：input_formats 不可為空", label));
+        }
+        if engine.output_formats.is_empty() {
+            errors.push(format!("{}：output_formats 不可為空", label));
+        }
+        if engine
+            .input_formats
+            .iter()
+            .chain(&engine.output_formats)
+            .any(|f| f.is_empty())
+        {
+            errors.push(format!("{}：格式名稱不可為空", label));
+        }
+        if engine.max_file_size_mb == 0 {
+            errors.push(format!("{}：max_file_size_mb 必須大於 0", label));
+        }
+
+        for capability in &engine.capabilities {
+            if !engine.input_formats.contains(&capability.input_format) {
+                errors.push(format!(
+                    "{}：capabilities 的輸入格式 {} 不在 input_formats 中",
+                    label, capability.input_format
+                ));
+            }
+            for output in &capability.output_formats {
+                if !engine.output_formats.contains(output) {
+                    errors.push(format!(
+                        "{}：capabilities 的輸出格式 {} 不在 output_formats 中",
+                        label, output
+                    ));
+                }
+            }
+        }
+
+        for key in engine.conversion_priorities.keys() {
+            let valid = key
+                .split_once("->")
+                .is_some_and(|(input, output)| !input.is_empty() && !output.is_empty());
+            if !valid {
+                errors.push(format!(
+                    "{}：conversion_priorities 的鍵 {} 必須是「輸入->輸出」格式",
+                    label, key
+                ));
+            }
+        }
+
+        if let Some(schema) = &engine.params_schema {
+            if !schema.is_object() {
+                errors.push(format!("{}：params_schema 必須是物件", label));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(CatalogError::Invalid(errors))
+    }
+}
+
+/// engine_id 是否只包含小寫英數字、`-` 與 `_`
+pub(crate) fn is_valid_engine_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// 統一格式名稱（小寫、去除開頭的 `.`）
+pub(crate) fn normalize_format(format: &str) -> String {
+    format.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// 統一引擎設定中的格式名稱
+fn normalize_engine(mut engine: Engine) -> Engine {
+    let normalize = |format: &mut String| *format = normalize_format(format);
+
+    engine.engine_id = engine.engine_id.trim().to_string();
+    engine.input_formats.iter_mut().for_each(normalize);
+    engine.output_formats.iter_mut().for_each(normalize);
+    for capability in engine.capabilities.iter_mut() {
+        normalize(&mut capability.input_format);
+        capability.output_formats.iter_mut().for_each(normalize);
+    }
+    engine.conversion_priorities = std::mem::take(&mut engine.conversion_priorities)
+        .into_iter()
+        .map(|(key, priority)| match key.split_once("->") {
+            Some((input, output)) => (
+                format!("{}->{}", normalize_format(input), normalize_format(output)),
+                priority,
+            ),
+            None => (key, priority),
+        })
+        .collect();
+    engine
+}
+
+/// 重新載入前後的引擎清單差異
+#[derive(Debug, Default, PartialEq)]
+pub struct CatalogDiff {
+    /// 新增的引擎
+    pub added: Vec<String>,
+    /// 移除的引擎
+    pub removed: Vec<String>,
+    /// 設定有變動的引擎
+    pub changed: Vec<EngineChange>,
+}
+
+impl CatalogDiff {
+    /// 比較兩份引擎清單
+    pub fn between(old: &[Engine], new: &[Engine]) -> Self {
+        let old: BTreeMap<&str, &Engine> = old.iter().map(|e| (e.engine_id.as_str(), e)).collect();
+        let new: BTreeMap<&str, &Engine> = new.iter().map(|e| (e.engine_id.as_str(), e)).collect();
+
+        let mut diff = Self::default();
+        for (id, engine) in &new {
+            match old.get(id) {
+                None => diff.added.push(id.to_string()),
+                Some(previous) if previous != engine => {
+                    diff.changed.push(EngineChange::between(previous, engine))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|id| !new.contains_key(*id))
+            .map(|id| id.to_string())
+            .collect();
+        diff
+    }
+
+    /// 是否完全沒有變動
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 單一引擎的設定變動
+#[derive(Debug, Default, PartialEq)]
+pub struct EngineChange {
+    pub engine_id: String,
+    /// 啟用狀態有變動時為新的狀態
+    pub enabled: Option<bool>,
+    /// 後端可用狀態有變動時為新的狀態
+    pub available: Option<bool>,
+    pub added_input_formats: Vec<String>,
+    pub removed_input_formats: Vec<String>,
+    pub added_output_formats: Vec<String>,
+    pub removed_output_formats: Vec<String>,
+}
+
+impl EngineChange {
+    fn between(old: &Engine, new: &Engine) -> Self {
+        let missing_from = |a: &[String], b: &[String]| -> Vec<String> {
+            a.iter().filter(|f| !b.contains(f)).cloned().collect()
+        };
+
+        Self {
+            engine_id: new.engine_id.clone(),
+            enabled: (old.enabled != new.enabled).then_some(new.enabled),
+            available: (old.available != new.available).then_some(new.available),
+            added_input_formats: missing_from(&new.input_formats, &old.input_formats),
+            removed_input_formats: missing_from(&old.input_formats, &new.input_formats),
+            added_output_formats: missing_from(&new.output_formats, &old.output_formats),
+            removed_output_formats: missing_from(&old.output_formats, &new.output_formats),
+        }
+    }
+}
+
+impl fmt::Display for EngineChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(enabled) = self.enabled {
+            parts.push(if enabled { "enabled" } else { "disabled" }.to_string());
+        }
+        if let Some(available) = self.available {
+            parts.push(
+                if available {
+                    "available"
+                } else {
+                    "unavailable"
+                }
+                .to_string(),
+            );
+        }
+        for (label, formats) in [
+            ("+input", &self.added_input_formats),
+            ("-input", &self.removed_input_formats),
+            ("+output", &self.added_output_formats),
+            ("-output", &self.removed_output_formats),
+        ] {
+            if !formats.is_empty() {
+                parts.push(format!("{} [{}]", label, formats.join(", ")));
+            }
+        }
+        if parts.is_empty() {
+            parts.push("settings updated".to_string());
+        }
+        write!(f, "{}: {}", self.engine_id, parts.join("; "))
+    }
+}
+
+/// 重新讀取設定檔並整批替換註冊表中的引擎
+///
+/// 設定檔無效時回傳錯誤，註冊表維持原狀。
+pub async fn reload_catalog(
+    registry: &EngineRegistry,
+    path: &Path,
+) -> Result<CatalogDiff, CatalogError> {
+    let engines = load_catalog(path)?;
+    Ok(registry.replace_engines(engines).await)
+}
+
+/// 啟動引擎設定檔監看
+///
+/// 每隔 `interval` 檢查檔案修改時間與大小（0 表示不輪詢），Unix 上另外接受 SIGHUP 強制重新載入。
+pub fn start_reloader(
+    registry: EngineRegistry,
+    path: PathBuf,
+    interval: Duration,
+) -> JoinHandle<()> {
+    // 在呼叫時記錄檔案狀態，任務開始執行前的變更也會被偵測到
+    let mut last_seen = file_stamp(&path);
+    tokio::spawn(async move {
+        let mut ticker = (!interval.is_zero()).then(|| tokio::time::interval(interval));
+        let mut hangup = listen_hangup();
+
+        loop {
+            let forced = tokio::select! {
+                _ = next_tick(&mut ticker) => false,
+                _ = hangup_received(&mut hangup) => true,
+            };
+
+            let stamp = file_stamp(&path);
+            if !forced && (stamp.is_none() || stamp == last_seen) {
+                continue;
+            }
+
+            // 無論成功與否都記錄檔案狀態，被拒絕的版本不會每次檢查都重新解析；
+            // 狀態包含檔案大小，修正後的檔案即使修改時間相同（檔案系統時間精度不足）也不會漏掉
+            last_seen = stamp;
+            match reload_catalog(&registry, &path).await {
+                Ok(diff) => {
+                    if diff.is_empty() {
+                        info!("🔧 Engine catalog {} reloaded (no changes)", path.display())
+                    } else {
+                        log_diff(&format!("catalog {}", path.display()), &diff)
+                    }
+                }
+                Err(e) => warn!(
+                    "❌ Engine catalog reload rejected, keeping current engines: {}",
+                    e
+                ),
+            }
+        }
+    })
+}
+
+/// 記錄引擎清單的變動
+pub(crate) fn log_diff(source: &str, diff: &CatalogDiff) {
+    if diff.is_empty() {
+        return;
+    }
+
+    info!("🔧 Engines updated from {}", source);
+    for id in &diff.added {
+        info!("  ➕ engine added: {}", id);
+    }
+    for id in &diff.removed {
+        info!("  ➖ engine removed: {}", id);
+    }
+    for change in &diff.changed {
+        info!("  🔄 {}", change);
+    }
+}
+
+/// 檔案的修改時間與大小，用來判斷設定檔是否變更
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+async fn next_tick(ticker: &mut Option<tokio::time::Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(unix)]
+type Hangup = tokio::signal::unix::Signal;
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(unix)]
+fn listen_hangup() -> Option<Hangup> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    signal(SignalKind::hangup())
+        .map_err(|e| warn!("Failed to listen for SIGHUP: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn listen_hangup() -> Option<Hangup> {
+    None
+}
+
+async fn hangup_received(hangup: &mut Option<Hangup>) {
+    match hangup {
+        #[cfg(unix)]
+        Some(signal) => {
+            if signal.recv().await.is_none() {
+                std::future::pending::<()>().await;
+            }
+        }
+        _ => std::future::pending().await,
+    }
+}
@@ -1,6 +1,14 @@
 //! 應用程式配置模組
 
+use std::collections::HashMap;
 use std::env;
+use std::time::Duration;
+
+use crate::janitor::RetentionPolicy;
+use crate::queue::QueueConfig;
+
+/// 預設的引擎並行上限（重量級引擎）
+const DEFAULT_ENGINE_CONCURRENCY: &str = "ffmpeg=2,mineru=1,babeldoc=1,pdfmathtranslate=1";
 
 /// 應用程式配置
 #[derive(Clone, Debug)]
@@ -17,6 +25,28 @@ pub struct AppConfig {
     pub upload_dir: String,
     /// 輸出目錄
     pub output_dir: String,
+    /// 任務資料庫路徑（SQLite）
+    pub database_path: String,
+    /// 引擎設定檔路徑（TOML 或 JSON，未設定時使用內建引擎）
+    pub engine_config_path: Option<String>,
+    /// 引擎設定檔變更檢查間隔（秒，0 表示僅透過 SIGHUP 重新載入）
+    pub engine_reload_interval_secs: u64,
+    /// 從後端同步引擎清單的間隔（秒，0 表示停用同步）
+    pub engine_sync_interval_secs: u64,
+    /// 同時執行的轉換任務上限
+    pub max_workers: usize,
+    /// 各引擎同時執行的任務上限
+    pub engine_concurrency: HashMap<String, usize>,
+    /// 每位使用者進行中的任務上限（0 表示不限制）
+    pub max_jobs_per_user: usize,
+    /// 已完成任務的保留時數
+    pub completed_job_ttl_hours: u64,
+    /// 失敗與已取消任務的保留時數
+    pub failed_job_ttl_hours: u64,
+    /// 過期任務清理間隔（秒，0 表示停用）
+    pub cleanup_interval_secs: u64,
+    /// 可續傳上傳閒置多久後過期（時數）
+    pub upload_session_ttl_hours: u64,
 }
 
 impl AppConfig {
@@ -39,6 +69,111 @@ impl AppConfig {
                 .unwrap_or_else(|_| "./data/uploads".to_string()),
             output_dir: env::var("OUTPUT_DIR")
                 .unwrap_or_else(|_| "./data/output".to_string()),
+            database_path: env::var("DATABASE_PATH")
+                .unwrap_or_else(|_| "./data/convertx-api.db".to_string()),
+            engine_config_path: env::var("ENGINE_CONFIG_PATH")
+                .ok()
+                .filter(|p| !p.is_empty()),
+            engine_reload_interval_secs: env::var("ENGINE_RELOAD_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            engine_sync_interval_secs: env::var("ENGINE_SYNC_INTERVAL_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            max_workers: env::var("MAX_CONCURRENT_JOBS")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4)
+                .max(1),
+            engine_concurrency: {
+                let mut limits = parse_engine_limits(DEFAULT_ENGINE_CONCURRENCY);
+                if let Ok(value) = env::var("ENGINE_CONCURRENCY") {
+                    limits.extend(parse_engine_limits(&value));
+                }
+                limits
+            },
+            max_jobs_per_user: env::var("MAX_JOBS_PER_USER")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            completed_job_ttl_hours: env::var("COMPLETED_JOB_TTL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
+            failed_job_ttl_hours: env::var("FAILED_JOB_TTL_HOURS")
+                .unwrap_or_else(|_| "72".to_string())
+                .parse()
+                .unwrap_or(72),
+            cleanup_interval_secs: env::var("CLEANUP_INTERVAL_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            upload_session_ttl_hours: env::var("UPLOAD_SESSION_TTL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
+        }
+    }
+
+    /// 任務佇列設定
+    pub fn queue_config(&self) -> QueueConfig {
+        QueueConfig {
+            max_workers: self.max_workers,
+            engine_limits: self.engine_concurrency.clone(),
+            max_jobs_per_user: self.max_jobs_per_user,
         }
     }
+
+    /// 可續傳上傳的閒置期限（秒）
+    pub fn upload_session_ttl_secs(&self) -> u64 {
+        self.upload_session_ttl_hours * 3600
+    }
+
+    /// 任務保留期限設定
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            completed_ttl: Duration::from_secs(self.completed_job_ttl_hours * 3600),
+            failed_ttl: Duration::from_secs(self.failed_job_ttl_hours * 3600),
+            interval: Duration::from_secs(self.cleanup_interval_secs),
+        }
+    }
+}
+
+impl Default for AppConfig {
+    /// 預設配置（JWT 密鑰為空，主要供測試使用）
+    fn default() -> Self {
+        Self {
+            port: 7890,
+            backend_url: "http://convertx:3000".to_string(),
+            jwt_secret: String::new(),
+            max_file_size: 524288000,
+            upload_dir: "./data/uploads".to_string(),
+            output_dir: "./data/output".to_string(),
+            database_path: "./data/convertx-api.db".to_string(),
+            engine_config_path: None,
+            engine_reload_interval_secs: 5,
+            engine_sync_interval_secs: 0,
+            max_workers: 4,
+            engine_concurrency: parse_engine_limits(DEFAULT_ENGINE_CONCURRENCY),
+            max_jobs_per_user: 20,
+            completed_job_ttl_hours: 24,
+            failed_job_ttl_hours: 72,
+            cleanup_interval_secs: 600,
+            upload_session_ttl_hours: 24,
+        }
+    }
+}
+
+/// 解析引擎並行上限（格式：`ffmpeg=2,mineru=1`），無效項目會被忽略
+pub fn parse_engine_limits(value: &str) -> HashMap<String, usize> {
+    value
+        .split(',')
+        .filter_map(|item| {
+            let (engine, limit) = item.split_once('=')?;
+            let limit = limit.trim().parse::<usize>().ok().filter(|l| *l > 0)?;
+            Some((engine.trim().to_lowercase(), limit))
+        })
+        .collect()
 }
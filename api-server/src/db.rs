@@ -0,0 +1,162 @@
+//! SQLite 資料庫模組
+//!
+//! 提供單一連線的 SQLite 存取與 schema 遷移。
+//! 所有查詢都透過 `spawn_blocking` 執行，避免阻塞 async runtime。
+
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::error::ApiError;
+
+/// Schema 遷移清單
+///
+/// 依序執行，已套用的版本記錄在 `PRAGMA user_version`。
+/// 只能在尾端新增，不可修改已發佈的遷移。
+const MIGRATIONS: &[&str] = &[
+    // v1：任務表
+    r#"
+    CREATE TABLE jobs (
+        job_id            TEXT PRIMARY KEY NOT NULL,
+        user_id           TEXT NOT NULL,
+        original_filename TEXT NOT NULL,
+        input_format      TEXT NOT NULL,
+        output_format     TEXT NOT NULL,
+        engine_id         TEXT NOT NULL,
+        status            TEXT NOT NULL,
+        progress          INTEGER NOT NULL DEFAULT 0,
+        error_message     TEXT,
+        output_file       TEXT,
+        created_at        INTEGER NOT NULL,
+        updated_at        INTEGER NOT NULL,
+        completed_at      INTEGER
+    );
+    CREATE INDEX idx_jobs_user_id ON jobs (user_id, created_at);
+    CREATE INDEX idx_jobs_status ON jobs (status);
+    "#,
+    // v2：保存轉換參數，供重啟後重新排入佇列
+    r#"
+    ALTER TABLE jobs ADD COLUMN options TEXT;
+    "#,
+    // v3：任務優先權
+    r#"
+    ALTER TABLE jobs ADD COLUMN priority TEXT NOT NULL DEFAULT 'normal';
+    "#,
+    // v4：自動選擇時的候選引擎（JSON 陣列）
+    r#"
+    ALTER TABLE jobs ADD COLUMN engine_candidates TEXT NOT NULL DEFAULT '[]';
+    "#,
+    // v5：多步驟轉換的步驟（JSON 陣列，空陣列表示單一步驟）
+    r#"
+    ALTER TABLE jobs ADD COLUMN steps TEXT NOT NULL DEFAULT '[]';
+    "#,
+    // v6：建立任務時的警告（JSON 陣列）
+    r#"
+    ALTER TABLE jobs ADD COLUMN warnings TEXT NOT NULL DEFAULT '[]';
+    "#,
+    // v7：可續傳上傳的工作階段
+    r#"
+    CREATE TABLE upload_sessions (
+        upload_id  TEXT PRIMARY KEY NOT NULL,
+        user_id    TEXT NOT NULL,
+        filename   TEXT NOT NULL,
+        size       INTEGER NOT NULL,
+        received   INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_upload_sessions_expires_at ON upload_sessions (expires_at);
+    "#,
+    // v8：輸出檔案的 SHA-256（下載的 ETag）
+    r#"
+    ALTER TABLE jobs ADD COLUMN output_checksum TEXT;
+    "#,
+];
+
+/// SQLite 資料庫
+#[derive(Clone)]
+pub struct Database {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Database {
+    /// 開啟（或建立）資料庫檔案並套用遷移
+    ///
+    /// 路徑為 `:memory:` 時使用記憶體資料庫。
+    pub fn open(path: &str) -> Result<Self, ApiError> {
+        let conn = if path == ":memory:" {
+            Connection::open_in_memory()
+        } else {
+            if let Some(parent) = Path::new(path).parent() {
+                if !parent.as_os_str().is_empty() {
+                    std::fs::create_dir_all(parent).map_err(|e| {
+                        ApiError::InternalError(format!("Failed to create database dir: {}", e))
+                    })?;
+                }
+            }
+            Connection::open(path)
+        }
+        .map_err(|e| ApiError::InternalError(format!("Failed to open database: {}", e)))?;
+
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000;")
+            .map_err(|e| ApiError::InternalError(format!("Failed to configure database: {}", e)))?;
+
+        let mut conn = conn;
+        migrate(&mut conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// 建立記憶體資料庫（測試用）
+    pub fn in_memory() -> Self {
+        Self::open(":memory:").expect("Failed to open in-memory database")
+    }
+
+    /// 在阻塞執行緒上執行資料庫操作
+    pub async fn call<F, T>(&self, f: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| ApiError::InternalError("Database lock poisoned".to_string()))?;
+            f(&mut conn).map_err(|e| ApiError::InternalError(format!("Database error: {}", e)))
+        })
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Database task failed: {}", e)))?
+    }
+
+    /// 目前的 schema 版本
+    pub async fn schema_version(&self) -> Result<usize, ApiError> {
+        self.call(|conn| conn.query_row("PRAGMA user_version", [], |row| row.get(0)))
+            .await
+    }
+}
+
+/// 套用尚未執行的遷移
+fn migrate(conn: &mut Connection) -> Result<(), ApiError> {
+    let current: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| ApiError::InternalError(format!("Failed to read schema version: {}", e)))?;
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        let version = index + 1;
+        let tx = conn
+            .transaction()
+            .map_err(|e| ApiError::InternalError(format!("Failed to start migration: {}", e)))?;
+        tx.execute_batch(sql)
+            .and_then(|_| tx.pragma_update(None, "user_version", version))
+            .and_then(|_| tx.commit())
+            .map_err(|e| {
+                ApiError::InternalError(format!("Migration v{} failed: {}", version, e))
+            })?;
+        tracing::info!("🗄️ Applied database migration v{}", version);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! 轉換結果下載模組
+//!
+//! 預設直接串流輸出檔案，依輸出格式設定 `Content-Type`（帶後綴的格式以實際輸出的檔案類型為準，如 `pdf-zh` 為 `pdf`、`png-300` 為 `tar`）；
+//! 指定 `?format=zip` 時才包裝成 ZIP，ZIP 在 blocking 執行緒建立，不佔用非同步執行緒。
+//! ZIP 內檔案的修改時間固定為任務完成時間，重新產生的 ZIP 內容不變，可沿用同一個 ETag。
+//!
+//! 支援 `Range`（單一範圍）與條件請求，讓下載工具可續傳、`<video>` 可直接拖曳播放：
+//! - `ETag` 由輸出檔案的 SHA-256 產生，`Last-Modified` 為任務完成時間
+//! - `If-None-Match` / `If-Modified-Since` 相符時回傳 `304 Not Modified`
+//! - `If-Range` 不相符時忽略 `Range`，回傳完整檔案
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::Response;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sha2::{Digest, Sha256};
+use std::io::SeekFrom;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use zip::{write::SimpleFileOptions, DateTime as ZipDateTime, ZipWriter};
+
+use crate::error::ApiError;
+
+/// RFC 5987 `filename*` 中不需編碼的字元（attr-char）
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// 下載的快取驗證資訊
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validators {
+    /// 強 ETag（含引號）
+    pub etag: String,
+    /// 最後修改時間（秒）
+    pub last_modified: Option<SystemTime>,
+}
+
+impl Validators {
+    /// 由檔案內容的識別值與任務完成時間（Unix 秒）建立
+    pub fn new(tag: &str, completed_at: Option<i64>) -> Self {
+        Self {
+            etag: format!("\"{}\"", tag),
+            last_modified: completed_at
+                .and_then(|t| u64::try_from(t).ok())
+                .map(|t| UNIX_EPOCH + Duration::from_secs(t)),
+        }
+    }
+}
+
+/// 依請求標頭決定回應的內容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// 快取仍有效
+    NotModified,
+    /// 完整檔案
+    Full,
+    /// 部分內容（起訖位置皆包含）
+    Partial { start: u64, end: u64 },
+}
+
+/// 依條件請求與 `Range` 標頭選擇回應內容
+///
+/// 範圍超出檔案時回傳 `RangeNotSatisfiable`；格式無法解析或含多個範圍時忽略 `Range`。
+pub fn select(
+    headers: &HeaderMap,
+    validators: &Validators,
+    len: u64,
+) -> Result<Selection, ApiError> {
+    if let Some(value) = header_str(headers, header::IF_NONE_MATCH) {
+        if etag_matches(value, &validators.etag, false) {
+            return Ok(Selection::NotModified);
+        }
+    } else if let Some(since) = header_date(headers, header::IF_MODIFIED_SINCE) {
+        if validators
+            .last_modified
+            .is_some_and(|modified| modified <= since)
+        {
+            return Ok(Selection::NotModified);
+        }
+    }
+
+    let Some(range) = header_str(headers, header::RANGE) else {
+        return Ok(Selection::Full);
+    };
+    if let Some(value) = header_str(headers, header::IF_RANGE) {
+        let current = if value.starts_with('"') {
+            etag_matches(value, &validators.etag, true)
+        } else {
+            httpdate::parse_http_date(value)
+                .is_ok_and(|date| validators.last_modified == Some(date))
+        };
+        if !current {
+            return Ok(Selection::Full);
+        }
+    }
+
+    match parse_range(range, len) {
+        Some(Some((start, end))) => Ok(Selection::Partial { start, end }),
+        Some(None) => Err(ApiError::RangeNotSatisfiable(len)),
+        None => Ok(Selection::Full),
+    }
+}
+
+/// 解析單一的 `bytes=` 範圍
+///
+/// 無法解析時回傳 None，範圍超出檔案時回傳 `Some(None)`。
+fn parse_range(value: &str, len: u64) -> Option<Option<(u64, u64)>> {
+    let (unit, spec) = value.trim().split_once('=')?;
+    if !unit.trim().eq_ignore_ascii_case("bytes") || spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.trim().split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(None);
+        }
+        return Some(Some((len.saturating_sub(suffix), len - 1)));
+    }
+
+    let start: u64 = start.parse().ok()?;
+    let end = match end {
+        "" => None,
+        end => Some(end.parse::<u64>().ok()?),
+    };
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+    if start >= len {
+        return Some(None);
+    }
+    Some(Some((start, end.map_or(len - 1, |end| end.min(len - 1)))))
+}
+
+/// ETag 是否符合 `If-None-Match` / `If-Range` 的清單
+///
+/// `strong` 時弱 ETag（`W/`）一律不相符。
+fn etag_matches(value: &str, etag: &str, strong: bool) -> bool {
+    value.split(',').map(str::trim).any(|candidate| {
+        if candidate == "*" {
+            return !strong;
+        }
+        match candidate.strip_prefix("W/") {
+            Some(weak) => !strong && weak == etag,
+            None => candidate == etag,
+        }
+    })
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn header_date(headers: &HeaderMap, name: header::HeaderName) -> Option<SystemTime> {
+    header_str(headers, name).and_then(|v| httpdate::parse_http_date(v).ok())
+}
+
+/// PDF Packager 選項實際產生的檔案類型
+///
+/// `pdfa1b-*` / `pdfa2b-*` 輸出 PDF，圖片（`png-300` 等）與 `all-*` 打包為 tar。
+const PACKAGER_OUTPUTS: &[(&str, &str)] = &[
+    ("pdfa1b", "pdf"),
+    ("pdfa2b", "pdf"),
+    ("png", "tar"),
+    ("jpg", "tar"),
+    ("jpeg", "tar"),
+    ("all", "tar"),
+];
+
+/// 帶後綴格式實際輸出的檔案類型
+///
+/// PDF Packager 選項依 [`PACKAGER_OUTPUTS`] 對應，其他取第一個 `-` 之前的部分，
+/// 例如 `pdf-zh` → `pdf`、`pdfa1b-i-300` → `pdf`、`png-300` → `tar`。
+pub fn base_format(format: &str) -> &str {
+    let Some((base, _)) = format.split_once('-') else {
+        return format;
+    };
+    PACKAGER_OUTPUTS
+        .iter()
+        .find(|(prefix, _)| base.eq_ignore_ascii_case(prefix))
+        .map_or(base, |(_, output)| output)
+}
+
+/// 依輸出格式取得 `Content-Type`，未知格式為 `application/octet-stream`
+pub fn content_type(format: &str) -> String {
+    let mime = mime_guess::from_ext(base_format(format)).first_or_octet_stream();
+    if mime.type_() == mime_guess::mime::TEXT && mime.get_param("charset").is_none() {
+        format!("{}; charset=utf-8", mime.essence_str())
+    } else {
+        mime.essence_str().to_string()
+    }
+}
+
+/// 下載時使用的檔名
+///
+/// 輸出檔案以格式為副檔名（例如 `doc.pdf-zh`），下載時改用實際的檔案類型（`doc.pdf`）。
+pub fn download_filename(output_path: &Path, format: &str) -> Option<String> {
+    let name = output_path.file_name()?.to_string_lossy();
+    let stem = name
+        .strip_suffix(format)
+        .and_then(|stem| stem.strip_suffix('.'));
+    Some(match stem {
+        Some(stem) => format!("{}.{}", stem, base_format(format)),
+        None => name.to_string(),
+    })
+}
+
+/// 產生 RFC 6266 的 `Content-Disposition`
+///
+/// `filename` 為 ASCII 替代名稱（非 ASCII 字元以 `_` 取代），
+/// `filename*` 以 UTF-8 百分比編碼保留原始檔名。
+pub fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    if fallback == filename {
+        format!("attachment; filename=\"{}\"", filename)
+    } else {
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback,
+            utf8_percent_encode(filename, ATTR_CHAR)
+        )
+    }
+}
+
+/// 串流回傳檔案，依請求標頭回傳完整內容、部分內容或 304
+pub async fn file_response(
+    path: &Path,
+    content_type: &str,
+    filename: &str,
+    validators: &Validators,
+    headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let io_error =
+        |e: std::io::Error| ApiError::InternalError(format!("Failed to read output file: {}", e));
+    let mut file = File::open(path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ApiError::FileNotFound(filename.to_string()),
+        _ => io_error(e),
+    })?;
+    let len = file.metadata().await.map_err(io_error)?.len();
+
+    let mut builder = Response::builder()
+        .header(header::ETAG, &validators.etag)
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Some(modified) = validators.last_modified {
+        builder = builder.header(header::LAST_MODIFIED, httpdate::fmt_http_date(modified));
+    }
+
+    let response = match select(headers, validators, len)? {
+        Selection::NotModified => builder.status(StatusCode::NOT_MODIFIED).body(Body::empty()),
+        Selection::Full => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, len)
+            .header(header::CONTENT_DISPOSITION, content_disposition(filename))
+            .body(Body::from_stream(ReaderStream::new(file))),
+        Selection::Partial { start, end } => {
+            file.seek(SeekFrom::Start(start)).await.map_err(io_error)?;
+            let length = end - start + 1;
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::CONTENT_LENGTH, length)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end, len),
+                )
+                .header(header::CONTENT_DISPOSITION, content_disposition(filename))
+                .body(Body::from_stream(ReaderStream::new(file.take(length))))
+        }
+    };
+    response.map_err(|e| ApiError::InternalError(format!("Failed to build response: {}", e)))
+}
+
+/// 計算檔案的 SHA-256（hex）
+pub async fn file_checksum(path: &Path) -> Result<String, ApiError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::open(&path).map_err(|e| open_error(&path, e))?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)
+            .map_err(|e| ApiError::InternalError(format!("Failed to read output file: {}", e)))?;
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    })
+    .await
+    .map_err(|e| ApiError::InternalError(format!("Failed to read output file: {}", e)))?
+}
+
+/// 將 `source` 以 `entry_name` 為檔名壓縮成 `zip_path`
+///
+/// 先寫入同目錄的暫存檔再改名，同時下載的請求不會讀到寫到一半的 ZIP。
+/// 檔案修改時間使用任務完成時間（UTC），相同輸出產生的 ZIP 逐位元組相同。
+pub async fn create_zip_file(
+    source: &Path,
+    zip_path: &Path,
+    entry_name: &str,
+    completed_at: Option<i64>,
+) -> Result<(), ApiError> {
+    let source = source.to_path_buf();
+    let zip_path = zip_path.to_path_buf();
+    let entry_name = entry_name.to_string();
+    let modified = zip_timestamp(completed_at);
+    tokio::task::spawn_blocking(move || write_zip(&source, &zip_path, &entry_name, modified))
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to create zip: {}", e)))?
+}
+
+/// ZIP 使用的時間（DOS 格式，UTC）；無法表示時為 1980-01-01
+pub fn zip_timestamp(completed_at: Option<i64>) -> ZipDateTime {
+    completed_at
+        .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+        .and_then(|t| {
+            use chrono::{Datelike, Timelike};
+            ZipDateTime::from_date_and_time(
+                u16::try_from(t.year()).ok()?,
+                t.month() as u8,
+                t.day() as u8,
+                t.hour() as u8,
+                t.minute() as u8,
+                t.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn write_zip(
+    source: &Path,
+    zip_path: &Path,
+    entry_name: &str,
+    modified: ZipDateTime,
+) -> Result<(), ApiError> {
+    let zip_error =
+        |e: &dyn std::fmt::Display| ApiError::InternalError(format!("Failed to create zip: {}", e));
+
+    let mut input = std::fs::File::open(source).map_err(|e| open_error(source, e))?;
+    let dir = zip_path.parent().unwrap_or(Path::new("."));
+    let temp = tempfile::NamedTempFile::new_in(dir).map_err(|e| zip_error(&e))?;
+
+    let mut zip = ZipWriter::new(temp);
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(modified)
+        .large_file(input.metadata().map_err(|e| zip_error(&e))?.len() >= u32::MAX as u64);
+
+    zip.start_file(entry_name, options)
+        .map_err(|e| zip_error(&e))?;
+    std::io::copy(&mut input, &mut zip).map_err(|e| zip_error(&e))?;
+    let temp = zip.finish().map_err(|e| zip_error(&e))?;
+    temp.persist(zip_path).map_err(|e| zip_error(&e.error))?;
+    Ok(())
+}
+
+/// 開啟輸出檔案失敗的錯誤（不暴露實體路徑）
+fn open_error(path: &Path, error: std::io::Error) -> ApiError {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => ApiError::FileNotFound(
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        ),
+        _ => ApiError::InternalError(format!("Failed to open output file: {}", error)),
+    }
+}
@@ -1,13 +1,15 @@
 //! 錯誤處理模組
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::params::ParamViolation;
+
 /// API 錯誤類型
 #[derive(Error, Debug)]
 pub enum ApiError {
@@ -47,15 +49,36 @@ pub enum ApiError {
     #[error("任務尚未完成：{0}")]
     JobNotReady(String),
 
+    #[error("進行中的任務過多：每位使用者最多 {0} 個")]
+    TooManyJobs(usize),
+
+    #[error("任務已結束：{0}")]
+    JobAlreadyFinished(String),
+
     #[error("任務執行失敗：{0}")]
     JobFailed(String),
 
     #[error("檔案不存在：{0}")]
     FileNotFound(String),
 
+    #[error("上傳工作階段不存在：{0}")]
+    UploadNotFound(String),
+
+    #[error("上傳衝突：{0}")]
+    UploadConflict(String),
+
+    #[error("區塊校驗失敗：SHA-256 不符")]
+    ChecksumMismatch,
+
+    #[error("請求範圍無效：檔案大小為 {0} bytes")]
+    RangeNotSatisfiable(u64),
+
     #[error("請求無效：{0}")]
     InvalidInput(String),
 
+    #[error("請求無效：轉換選項不符合引擎參數格式（{} 項）", .0.len())]
+    InvalidOptions(Vec<ParamViolation>),
+
     #[error("內部錯誤：{0}")]
     InternalError(String),
 
@@ -69,6 +92,9 @@ pub struct ErrorResponse {
     pub error: String,
     pub code: String,
     pub message: String,
+    /// 錯誤細節（例如逐項列出的參數驗證錯誤）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 impl IntoResponse for ApiError {
@@ -88,20 +114,42 @@ impl IntoResponse for ApiError {
             ApiError::UnsupportedFormat(_) => (StatusCode::BAD_REQUEST, "UNSUPPORTED_FORMAT"),
             ApiError::JobNotFound(_) => (StatusCode::NOT_FOUND, "JOB_NOT_FOUND"),
             ApiError::JobNotReady(_) => (StatusCode::BAD_REQUEST, "JOB_NOT_READY"),
+            ApiError::TooManyJobs(_) => (StatusCode::TOO_MANY_REQUESTS, "TOO_MANY_JOBS"),
+            ApiError::JobAlreadyFinished(_) => (StatusCode::CONFLICT, "JOB_ALREADY_FINISHED"),
             ApiError::JobFailed(_) => (StatusCode::INTERNAL_SERVER_ERROR, "JOB_FAILED"),
             ApiError::FileNotFound(_) => (StatusCode::NOT_FOUND, "FILE_NOT_FOUND"),
-            ApiError::InvalidInput(_) => (StatusCode::BAD_REQUEST, "INVALID_INPUT"),
+            ApiError::UploadNotFound(_) => (StatusCode::NOT_FOUND, "UPLOAD_NOT_FOUND"),
+            ApiError::UploadConflict(_) => (StatusCode::CONFLICT, "UPLOAD_CONFLICT"),
+            ApiError::ChecksumMismatch => (StatusCode::BAD_REQUEST, "CHECKSUM_MISMATCH"),
+            ApiError::RangeNotSatisfiable(_) => {
+                (StatusCode::RANGE_NOT_SATISFIABLE, "RANGE_NOT_SATISFIABLE")
+            }
+            ApiError::InvalidInput(_) | ApiError::InvalidOptions(_) => {
+                (StatusCode::BAD_REQUEST, "INVALID_INPUT")
+            }
             ApiError::InternalError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
             ApiError::BackendError(_) => (StatusCode::BAD_GATEWAY, "BACKEND_ERROR"),
         };
 
+        let details = match &self {
+            ApiError::InvalidOptions(violations) => serde_json::to_value(violations).ok(),
+            _ => None,
+        };
+
         let body = ErrorResponse {
             error: code.to_string(),
             code: code.to_string(),
             message: self.to_string(),
+            details,
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let ApiError::RangeNotSatisfiable(len) = self {
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", len)) {
+                response.headers_mut().insert(header::CONTENT_RANGE, value);
+            }
+        }
+        response
     }
 }
 
@@ -3,8 +3,8 @@
 use async_graphql::{Context, EmptySubscription, Object, Schema, SimpleObject, InputObject, Enum};
 use chrono::{DateTime, Utc};
 
-use crate::auth::AppState;
-use crate::models::JobStatus as ModelJobStatus;
+use crate::auth::{AppState, AuthenticatedUser};
+use crate::models::{JobPriority as ModelJobPriority, JobStatus as ModelJobStatus};
 
 /// GraphQL Schema 類型
 pub type ApiSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
@@ -31,14 +31,22 @@ pub struct Engine {
     pub description: String,
     /// 是否啟用
     pub enabled: bool,
+    /// 後端是否提供
+    pub available: bool,
     /// 支援的輸入格式
     pub input_formats: Vec<String>,
     /// 支援的輸出格式
     pub output_formats: Vec<String>,
     /// 最大檔案大小（MB）
     pub max_file_size_mb: i32,
+    /// 轉換逾時（秒）
+    pub timeout_secs: Option<i32>,
+    /// 自動選擇時的優先順序（數字越大越優先）
+    pub priority: i32,
     /// 是否需要額外參數
     pub requires_params: bool,
+    /// 引擎參數說明（JSON Schema）
+    pub params_schema: Option<async_graphql::Json<serde_json::Value>>,
 }
 
 /// 任務狀態
@@ -52,6 +60,8 @@ pub enum JobStatus {
     Completed,
     /// 失敗
     Failed,
+    /// 已取消
+    Cancelled,
 }
 
 impl From<ModelJobStatus> for JobStatus {
@@ -61,6 +71,28 @@ impl From<ModelJobStatus> for JobStatus {
             ModelJobStatus::Processing => JobStatus::Processing,
             ModelJobStatus::Completed => JobStatus::Completed,
             ModelJobStatus::Failed => JobStatus::Failed,
+            ModelJobStatus::Cancelled => JobStatus::Cancelled,
+        }
+    }
+}
+
+/// 任務優先權
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum JobPriority {
+    /// 低
+    Low,
+    /// 一般
+    Normal,
+    /// 高
+    High,
+}
+
+impl From<ModelJobPriority> for JobPriority {
+    fn from(priority: ModelJobPriority) -> Self {
+        match priority {
+            ModelJobPriority::Low => JobPriority::Low,
+            ModelJobPriority::Normal => JobPriority::Normal,
+            ModelJobPriority::High => JobPriority::High,
         }
     }
 }
@@ -80,8 +112,16 @@ pub struct Job {
     pub output_format: String,
     /// 使用的引擎 ID
     pub engine_id: String,
+    /// 支援此轉換的引擎，依選擇順序排列
+    pub engine_candidates: Vec<String>,
+    /// 轉換步驟（多個引擎串接時有多個）
+    pub steps: Vec<JobStep>,
+    /// 建立任務時的警告
+    pub warnings: Vec<String>,
     /// 任務狀態
     pub status: JobStatus,
+    /// 優先權
+    pub priority: JobPriority,
     /// 進度（0-100）
     pub progress: i32,
     /// 錯誤訊息
@@ -90,10 +130,23 @@ pub struct Job {
     pub created_at: DateTime<Utc>,
     /// 更新時間
     pub updated_at: DateTime<Utc>,
-    /// 完成時間
+    /// 結束時間（完成、失敗或取消）
     pub completed_at: Option<DateTime<Utc>>,
     /// 是否可下載
     pub download_ready: bool,
+    /// 在佇列中的位置（從 1 開始）
+    pub queue_position: Option<i32>,
+}
+
+/// 轉換步驟
+#[derive(SimpleObject, Clone)]
+pub struct JobStep {
+    /// 執行此步驟的引擎 ID
+    pub engine_id: String,
+    /// 輸入格式
+    pub input_format: String,
+    /// 輸出格式
+    pub output_format: String,
 }
 
 /// 健康狀態
@@ -187,41 +240,62 @@ impl QueryRoot {
     async fn engines(&self, ctx: &Context<'_>) -> Vec<Engine> {
         let state = ctx.data::<AppState>().unwrap();
         let engines = state.engine_registry.list_engines().await;
-        
-        engines.into_iter().map(|e| Engine {
-            id: e.engine_id,
-            name: e.engine_name,
-            description: e.description,
-            enabled: e.enabled,
-            input_formats: e.input_formats,
-            output_formats: e.output_formats,
-            max_file_size_mb: e.max_file_size_mb as i32,
-            requires_params: e.requires_params,
-        }).collect()
-    }
 
+        engines
+            .into_iter()
+            .map(|e| Engine {
+                id: e.engine_id,
+                name: e.engine_name,
+                description: e.description,
+                enabled: e.enabled,
+                available: e.available,
+                input_formats: e.input_formats,
+                output_formats: e.output_formats,
+                max_file_size_mb: e.max_file_size_mb as i32,
+                timeout_secs: e.timeout_secs.map(|t| t as i32),
+                priority: e.priority,
+                requires_params: e.requires_params,
+                params_schema: e.params_schema.map(async_graphql::Json),
+            })
+            .collect()
+    }
+        
     /// 取得特定引擎
     async fn engine(&self, ctx: &Context<'_>, id: String) -> Option<Engine> {
         let state = ctx.data::<AppState>().unwrap();
         let engine = state.engine_registry.get_engine(&id).await?;
-        
+
         Some(Engine {
             id: engine.engine_id,
             name: engine.engine_name,
             description: engine.description,
             enabled: engine.enabled,
+            available: engine.available,
             input_formats: engine.input_formats,
             output_formats: engine.output_formats,
             max_file_size_mb: engine.max_file_size_mb as i32,
+            timeout_secs: engine.timeout_secs.map(|t| t as i32),
+            priority: engine.priority,
             requires_params: engine.requires_params,
+            params_schema: engine.params_schema.map(async_graphql::Json),
         })
     }
-
+        
     /// 取得任務狀態
     async fn job(&self, ctx: &Context<'_>, id: String) -> Option<Job> {
         let state = ctx.data::<AppState>().unwrap();
         let job = state.job_store.get_job(&id).await?;
-        
+
+        let steps = job
+            .conversion_steps()
+            .into_iter()
+            .map(|step| JobStep {
+                engine_id: step.engine_id,
+                input_format: step.input_format,
+                output_format: step.output_format,
+            })
+            .collect();
+
         Some(Job {
             id: job.job_id,
             user_id: job.user_id,
@@ -229,13 +303,18 @@ impl QueryRoot {
             input_format: job.input_format,
             output_format: job.output_format,
             engine_id: job.engine_id,
+            engine_candidates: job.engine_candidates,
+            steps,
+            warnings: job.warnings,
             status: job.status.into(),
+            priority: job.priority.into(),
             progress: job.progress as i32,
             error_message: job.error_message,
             created_at: DateTime::from_timestamp(job.created_at, 0).unwrap_or_default(),
             updated_at: DateTime::from_timestamp(job.updated_at, 0).unwrap_or_default(),
             completed_at: job.completed_at.and_then(|t| DateTime::from_timestamp(t, 0)),
             download_ready: job.status == ModelJobStatus::Completed && job.output_file.is_some(),
+            queue_position: state.job_queue.position(&id).await.map(|p| p as i32),
         })
     }
 
@@ -297,44 +376,42 @@ pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
-    /// 刪除任務
-    async fn delete_job(&self, ctx: &Context<'_>, id: String) -> bool {
+    /// 刪除任務及其檔案（需為任務擁有者）
+    async fn delete_job(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<bool> {
         let state = ctx.data::<AppState>().unwrap();
-        
-        // 目前 JobStore 沒有刪除方法，返回 false
-        // TODO: 實作刪除功能
-        if state.job_store.get_job(&id).await.is_some() {
-            // 任務存在但無法刪除
-            false
-        } else {
-            false
-        }
+        let user = ctx
+            .data_opt::<AuthenticatedUser>()
+            .ok_or_else(|| async_graphql::Error::new("缺少授權標頭"))?;
+
+        crate::handlers::request_deletion(state, &user.user_id, &id)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        Ok(true)
     }
 
-    /// 取消任務（僅限等待中的任務）
-    async fn cancel_job(&self, ctx: &Context<'_>, id: String) -> bool {
+    /// 取消任務（等待中或處理中的任務，需為任務擁有者）
+    async fn cancel_job(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<bool> {
         let state = ctx.data::<AppState>().unwrap();
-        
-        if let Some(job) = state.job_store.get_job(&id).await {
-            if job.status == ModelJobStatus::Pending {
-                // 更新狀態為失敗
-                state.job_store.fail_job(&id, "已取消".to_string()).await;
-                return true;
-            }
-        }
-        false
+        let user = ctx
+            .data_opt::<AuthenticatedUser>()
+            .ok_or_else(|| async_graphql::Error::new("缺少授權標頭"))?;
+
+        crate::handlers::request_cancellation(state, &user.user_id, &id)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        Ok(true)
     }
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
-
+    
 /// 檢查後端狀態
 async fn check_backend(state: &AppState) -> Result<(), String> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/health", state.config.backend_url);
-    
+
     let response = client
         .get(&url)
         .timeout(std::time::Duration::from_secs(5))
@@ -351,11 +428,13 @@ async fn check_backend(state: &AppState) -> Result<(), String> {
 
 /// 取得轉換建議
 async fn get_suggestions(state: &AppState, from: &str, to: &str) -> Vec<ConversionSuggestion> {
-    let engines = state.engine_registry.list_engines().await;
-    
+    let engines = state
+        .engine_registry
+        .rank_engines_for_conversion(from, to)
+        .await;
+
     engines
         .into_iter()
-        .filter(|e| e.enabled && e.supports_conversion(from, to))
         .map(|e| ConversionSuggestion {
             engine: e.engine_id.clone(),
             engine_name: e.engine_name.clone(),
@@ -2,25 +2,57 @@
 
 use axum::{
     body::Body,
-    extract::{Multipart, Path, State},
-    http::{header, StatusCode},
+    extract::{
+        multipart::MultipartError,
+        rejection::{JsonRejection, QueryRejection},
+        Multipart, Path, Query, State,
+    },
+    http::{HeaderMap, StatusCode},
     response::Response,
     Json,
 };
+use futures::StreamExt;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use tokio_util::io::ReaderStream;
-use zip::{write::SimpleFileOptions, ZipWriter};
+use tokio_util::sync::CancellationToken;
 
 use crate::auth::AppState;
 use crate::auth::AuthenticatedUser;
-use crate::engine::EngineInfo;
+use crate::download::{
+    base_format, content_type, create_zip_file, download_filename, file_checksum, file_response,
+    Validators,
+};
+use crate::engine::{EngineInfo, EngineOverride};
 use crate::error::ApiError;
+use crate::job::{JobCursor, JobFilter};
 use crate::models::{
-    ApiResponse, ConvertParams, ConvertResponse, EngineDetailResponse,
-    EnginesListResponse, HealthResponse, Job, JobStatus, JobStatusResponse,
+    ApiResponse, ConversionPlanRequest, ConversionPlanResponse, ConvertParams, ConvertResponse,
+    CreateUploadRequest, DeleteJobResponse, DeleteUploadResponse, DownloadFormat, DownloadQuery,
+    EngineConversionsResponse, EngineDetailResponse, EnginesListResponse, HealthResponse, Job,
+    JobListQuery, JobListResponse, JobPriority, JobStatus, JobStatusResponse,
+};
+use crate::params::{declared_options, validate_options, ParamViolation};
+use crate::planner::{ConversionPlan, ConversionStep};
+use crate::queue::QueuedJob;
+use crate::resumable::{
+    is_valid_filename, parse_checksum, session_path, write_chunk, UploadSession,
+    UPLOAD_CHECKSUM_HEADER, UPLOAD_OFFSET_HEADER,
 };
+use crate::sniff::{detect_input_format, read_head};
+use crate::upload::{stage_upload, StagedUpload};
+
+/// 任務列表預設每頁筆數
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// 任務列表每頁筆數上限
+const MAX_PAGE_SIZE: usize = 100;
+
+/// 引擎未設定逾時時的轉換逾時（秒）
+const DEFAULT_CONVERT_TIMEOUT_SECS: u64 = 300;
 
 /// 健康檢查
 pub async fn health_check(State(state): State<AppState>) -> Json<ApiResponse<HealthResponse>> {
@@ -34,6 +66,7 @@ pub async fn health_check(State(state): State<AppState>) -> Json<ApiResponse<Hea
         status: "healthy".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         backend_status,
+        retention: state.retention_stats.totals(),
     }))
 }
 
@@ -88,6 +121,70 @@ pub async fn get_engine(
     })))
 }
 
+/// 取得引擎的轉換對照表
+pub async fn get_engine_conversions(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    Path(engine_id): Path<String>,
+) -> Result<Json<ApiResponse<EngineConversionsResponse>>, ApiError> {
+    let engine = state
+        .engine_registry
+        .get_engine(&engine_id)
+        .await
+        .ok_or(ApiError::EngineNotFound(engine_id))?;
+
+    Ok(Json(ApiResponse::success(EngineConversionsResponse {
+        conversions: engine.conversion_matrix(),
+        engine_id: engine.engine_id,
+    })))
+}
+
+/// 調整引擎設定（需要 `admin` 權限）
+pub async fn update_engine(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(engine_id): Path<String>,
+    body: Result<Json<EngineOverride>, JsonRejection>,
+) -> Result<Json<ApiResponse<EngineDetailResponse>>, ApiError> {
+    if !user.is_admin() {
+        return Err(ApiError::Forbidden("Missing 'admin' scope".to_string()));
+    }
+
+    let Json(overrides) = body.map_err(|e| ApiError::InvalidInput(e.body_text()))?;
+    if overrides.is_empty() {
+        return Err(ApiError::InvalidInput(
+            "No engine settings to update".to_string(),
+        ));
+    }
+    if overrides.max_file_size_mb == Some(0) {
+        return Err(ApiError::InvalidInput(
+            "max_file_size_mb must be greater than 0".to_string(),
+        ));
+    }
+    if overrides.timeout_secs == Some(0) {
+        return Err(ApiError::InvalidInput(
+            "timeout_secs must be greater than 0".to_string(),
+        ));
+    }
+
+    let engine = state
+        .engine_registry
+        .update_engine(&engine_id, overrides.clone())
+        .await
+        .ok_or(ApiError::EngineNotFound(engine_id))?;
+
+    tracing::info!(
+        "🛠️ Engine {} updated by {}: {:?}",
+        engine.engine_id,
+        user.user_id,
+        overrides
+    );
+
+    Ok(Json(ApiResponse::success(EngineDetailResponse {
+        engine: EngineInfo::from(&engine),
+    })))
+}
+
 /// 建立轉換任務
 pub async fn create_conversion(
     State(state): State<AppState>,
@@ -99,14 +196,17 @@ pub async fn create_conversion(
         return Err(ApiError::Forbidden("Missing 'convert' scope".to_string()));
     }
 
-    let mut file_data: Option<(String, Vec<u8>)> = None;
+    // 檢查使用者進行中任務數（在讀取上傳內容前先拒絕）
+    state.job_queue.check_user_quota(&user.user_id).await?;
+
+    let mut upload: Option<(String, StagedUpload)> = None;
     let mut params: Option<ConvertParams> = None;
 
     // 解析 multipart 表單
-    while let Some(field) = multipart
+    while let Some(mut field) = multipart
         .next_field()
         .await
-        .map_err(|e| ApiError::InvalidInput(format!("Failed to read multipart: {}", e)))?
+        .map_err(|e| multipart_error(&state, "Failed to read multipart", e))?
     {
         let name = field.name().unwrap_or("").to_string();
 
@@ -116,18 +216,25 @@ pub async fn create_conversion(
                     .file_name()
                     .ok_or_else(|| ApiError::InvalidInput("Missing filename".to_string()))?
                     .to_string();
-                
-                let data = field
-                    .bytes()
-                    .await
-                    .map_err(|e| ApiError::InvalidInput(format!("Failed to read file: {}", e)))?;
+                // 檔名會成為任務目錄下的路徑，含路徑的檔名在讀取內容前即拒絕
+                if !is_valid_filename(&filename) {
+                    return Err(ApiError::InvalidInput(format!(
+                        "Invalid filename: {}",
+                        filename
+                    )));
+                }
 
-                // 檢查檔案大小
-                if data.len() > state.config.max_file_size as usize {
-                    return Err(ApiError::FileTooLarge(state.config.max_file_size));
+                // 參數在檔案之前且指定引擎時，寫入期間一併套用引擎的大小上限
+                let mut max_size = state.config.max_file_size;
+                if let Some(id) = params.as_ref().and_then(|p| p.engine_id.as_deref()) {
+                    if let Some(engine) = state.engine_registry.get_engine(id).await {
+                        max_size = max_size.min(engine.max_file_size_bytes());
+                    }
                 }
 
-                file_data = Some((filename, data.to_vec()));
+                let upload_root = PathBuf::from(&state.config.upload_dir);
+                let staged = stage_upload(&mut field, &upload_root, max_size).await?;
+                upload = Some((filename, staged));
             }
             "params" | "options" => {
                 let text = field
@@ -145,110 +252,556 @@ pub async fn create_conversion(
     }
 
     // 驗證必要欄位
-    let (filename, data) = file_data.ok_or_else(|| ApiError::InvalidInput("Missing file".to_string()))?;
+    let (filename, upload) =
+        upload.ok_or_else(|| ApiError::InvalidInput("Missing file".to_string()))?;
     let params = params.ok_or_else(|| ApiError::InvalidInput("Missing params".to_string()))?;
 
-    // 取得輸入格式
-    let input_format = PathBuf::from(&filename)
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|s| s.to_lowercase())
-        .ok_or_else(|| ApiError::InvalidInput("Cannot determine file format".to_string()))?;
-
-    // 選擇引擎
-    let engine_id = if let Some(ref id) = params.engine_id {
-        // 驗證指定的引擎
-        let engine = state
-            .engine_registry
-            .get_engine(id)
-            .await
-            .ok_or_else(|| ApiError::EngineNotFound(id.clone()))?;
+    let job = prepare_job(&state, &user, filename, &upload.head, upload.size, params).await?;
 
-        if !engine.supports_conversion(&input_format, &params.output_format) {
-            return Err(ApiError::UnsupportedConversion {
-                from: input_format.clone(),
-                to: params.output_format.clone(),
-            });
+    // 先將暫存的上傳檔案移到任務目錄再儲存任務，任一步失敗時不留下沒有檔案的任務
+    let job_id = job.job_id.clone();
+    let result = async {
+        upload
+            .persist(&job_upload_path(&state, &job).await?)
+            .await?;
+        state.job_store.create_job(job.clone()).await?;
+        enqueue_job(&state, job).await
+    }
+    .await;
+    match result {
+        Ok(response) => Ok(Json(ApiResponse::success(response))),
+        Err(e) => {
+            discard_job(&state, &job_id).await;
+            Err(e)
         }
-        id.clone()
-    } else {
-        // 自動選擇引擎
-        let engine = state
-            .engine_registry
-            .find_engine_for_conversion(&input_format, &params.output_format)
-            .await
-            .ok_or_else(|| ApiError::UnsupportedConversion {
-                from: input_format.clone(),
-                to: params.output_format.clone(),
-            })?;
-        engine.engine_id
-    };
+    }
+}
 
-    // 建立任務
-    let job = Job::new(
+/// 依上傳檔案與轉換參數建立任務（尚未儲存）
+///
+/// `head` 為檔案開頭，用於判斷輸入格式；`size` 用於檢查引擎的檔案大小上限。
+async fn prepare_job(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    filename: String,
+    head: &[u8],
+    size: u64,
+    params: ConvertParams,
+) -> Result<Job, ApiError> {
+    // 檢查優先權
+    let priority = params.priority.unwrap_or_default();
+    if priority == JobPriority::High && !user.can_use_high_priority() {
+        return Err(ApiError::Forbidden(
+            "Missing 'priority:high' scope".to_string(),
+        ));
+    }
+
+    // 取得輸入格式（副檔名缺少或與內容不符時依檔案內容判斷）
+    let detected = detect_input_format(&filename, head)
+        .ok_or_else(|| ApiError::InvalidInput("Cannot determine file format".to_string()))?;
+    let input_format = detected.format;
+    let warnings: Vec<String> = detected.warning.into_iter().collect();
+    for warning in &warnings {
+        tracing::info!("🔍 Upload {} from {}: {}", filename, user.user_id, warning);
+    }
+
+    // 規劃轉換步驟並驗證選項
+    let plan = select_plan(
+        state,
+        &input_format,
+        &params.output_format,
+        params.engine_id.as_deref(),
+        size,
+    )
+    .await?;
+    validate_plan_options(state, &plan, params.options.as_ref()).await?;
+    let engine_id = plan.first_engine_id().to_string();
+    let engine_candidates = rank_engine_ids(state, &input_format, &params.output_format).await;
+
+    Ok(Job::new(
         user.user_id.clone(),
-        filename.clone(),
-        input_format.clone(),
-        params.output_format.clone(),
-        engine_id.clone(),
-    );
-    let job_id = job.job_id.clone();
+        filename,
+        input_format,
+        params.output_format,
+        engine_id,
+    )
+    .with_options(params.options)
+    .with_priority(priority)
+    .with_engine_candidates(engine_candidates)
+    .with_steps(plan.steps)
+    .with_warnings(warnings))
+}
 
-    // 儲存任務
-    state.job_store.create_job(job).await;
+/// 捨棄無法建立或排入佇列的任務：刪除任務記錄（若已儲存）與上傳目錄
+async fn discard_job(state: &AppState, job_id: &str) {
+    state.job_store.delete_job(job_id).await;
+    remove_job_files(state, job_id).await;
+}
 
-    // 儲存上傳檔案
-    let upload_dir = PathBuf::from(&state.config.upload_dir).join(&job_id);
+/// 建立任務的上傳目錄，回傳上傳檔案應放置的路徑
+async fn job_upload_path(state: &AppState, job: &Job) -> Result<PathBuf, ApiError> {
+    let upload_dir = PathBuf::from(&state.config.upload_dir).join(&job.job_id);
     tokio::fs::create_dir_all(&upload_dir)
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to create upload dir: {}", e)))?;
+    Ok(upload_dir.join(job.upload_filename()))
+}
 
-    let upload_path = upload_dir.join(&filename);
-    let mut file = File::create(&upload_path)
+/// 將已儲存的任務排入轉換佇列（超過每人進行中任務上限時回傳錯誤）
+async fn enqueue_job(state: &AppState, job: Job) -> Result<ConvertResponse, ApiError> {
+    state.job_queue.enqueue(QueuedJob::for_job(&job)).await?;
+
+    Ok(ConvertResponse {
+        job_id: job.job_id,
+        engine_id: job.engine_id,
+        engine_candidates: job.engine_candidates,
+        warnings: job.warnings,
+        status: "pending".to_string(),
+        message: "Conversion job created".to_string(),
+    })
+}
+
+/// 建立可續傳上傳
+pub async fn create_upload(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    body: Result<Json<CreateUploadRequest>, JsonRejection>,
+) -> Result<Json<ApiResponse<UploadSession>>, ApiError> {
+    if !user.can_convert() {
+        return Err(ApiError::Forbidden("Missing 'convert' scope".to_string()));
+    }
+    let Json(request) = body.map_err(|e| ApiError::InvalidInput(e.body_text()))?;
+
+    if !is_valid_filename(&request.filename) {
+        return Err(ApiError::InvalidInput(format!(
+            "Invalid filename: {}",
+            request.filename
+        )));
+    }
+    if request.size == 0 {
+        return Err(ApiError::InvalidInput(
+            "Upload size must be greater than 0".to_string(),
+        ));
+    }
+    if request.size > state.config.max_file_size {
+        return Err(ApiError::FileTooLarge(state.config.max_file_size));
+    }
+
+    let session = UploadSession::new(
+        user.user_id.clone(),
+        request.filename,
+        request.size,
+        state.config.upload_session_ttl_secs(),
+    );
+    let path = session_path(&state.config.upload_dir, &session.upload_id);
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to create upload dir: {}", e)))?;
+    }
+    File::create(&path)
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to create file: {}", e)))?;
-    
-    file.write_all(&data)
+
+    let session = state.upload_sessions.create(session).await?;
+    Ok(Json(ApiResponse::success(session)))
+}
+
+/// 查詢可續傳上傳的進度
+pub async fn get_upload(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(upload_id): Path<String>,
+) -> Result<Json<ApiResponse<UploadSession>>, ApiError> {
+    let session = owned_upload(&state, &user, &upload_id).await?;
+    Ok(Json(ApiResponse::success(session)))
+}
+
+/// 上傳區塊
+///
+/// 請求內容為區塊資料，`Upload-Offset` 必須等於目前已接收的 bytes，
+/// `Upload-Checksum` 為區塊的 `sha256 <hex>`。校驗不符時區塊不會被接收。
+pub async fn upload_chunk(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ApiResponse<UploadSession>>, ApiError> {
+    owned_upload(&state, &user, &upload_id).await?;
+
+    let offset: u64 = headers
+        .get(UPLOAD_OFFSET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or_else(|| {
+            ApiError::InvalidInput("Missing or invalid Upload-Offset header".to_string())
+        })?;
+    let checksum = headers
+        .get(UPLOAD_CHECKSUM_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| ApiError::InvalidInput("Missing Upload-Checksum header".to_string()))
+        .and_then(parse_checksum)?;
+
+    let _lock = state
+        .upload_sessions
+        .lock(&upload_id)
+        .ok_or_else(|| ApiError::UploadConflict("Another chunk is being uploaded".to_string()))?;
+
+    // 取得鎖後重新讀取，確保位移是最新的
+    let session = owned_upload(&state, &user, &upload_id).await?;
+    if offset != session.offset {
+        return Err(ApiError::UploadConflict(format!(
+            "Upload-Offset {} does not match current offset {}",
+            offset, session.offset
+        )));
+    }
+
+    let path = session_path(&state.config.upload_dir, &upload_id);
+    let end = write_chunk(
+        &path,
+        offset,
+        session.size,
+        body.into_data_stream(),
+        &checksum,
+    )
+    .await?;
+
+    let expires_at = chrono::Utc::now().timestamp() + state.config.upload_session_ttl_secs() as i64;
+    let session = state
+        .upload_sessions
+        .advance(&upload_id, end, expires_at)
         .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to write file: {}", e)))?;
-
-    // 啟動後台轉換任務
-    let state_clone = state.clone();
-    let job_id_clone = job_id.clone();
-    let upload_path_clone = upload_path.clone();
-    let output_format = params.output_format.clone();
-    let options = params.options.clone();
-
-    tokio::spawn(async move {
-        process_conversion(
-            state_clone,
-            job_id_clone,
-            upload_path_clone,
-            output_format,
-            engine_id,
-            options,
-        )
-        .await;
-    });
+        .ok_or_else(|| ApiError::UploadNotFound(upload_id.clone()))?;
+    Ok(Json(ApiResponse::success(session)))
+}
 
-    Ok(Json(ApiResponse::success(ConvertResponse {
-        job_id,
-        status: "pending".to_string(),
-        message: "Conversion job created".to_string(),
+/// 取消可續傳上傳
+pub async fn delete_upload(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(upload_id): Path<String>,
+) -> Result<Json<ApiResponse<DeleteUploadResponse>>, ApiError> {
+    owned_upload(&state, &user, &upload_id).await?;
+    let _lock = state
+        .upload_sessions
+        .lock(&upload_id)
+        .ok_or_else(|| ApiError::UploadConflict("A chunk is being uploaded".to_string()))?;
+
+    state
+        .upload_sessions
+        .delete(&upload_id)
+        .await
+        .ok_or_else(|| ApiError::UploadNotFound(upload_id.clone()))?;
+    let _ = tokio::fs::remove_file(session_path(&state.config.upload_dir, &upload_id)).await;
+
+    Ok(Json(ApiResponse::success(DeleteUploadResponse {
+        upload_id,
+        message: "Upload deleted".to_string(),
     })))
 }
 
+/// 完成可續傳上傳並建立轉換任務
+///
+/// 請求內容為轉換參數（與 `POST /api/v1/convert` 的 `params` 相同）。
+/// 建立任務失敗時工作階段保留，可修正參數後再次完成。
+pub async fn complete_upload(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(upload_id): Path<String>,
+    body: Result<Json<ConvertParams>, JsonRejection>,
+) -> Result<Json<ApiResponse<ConvertResponse>>, ApiError> {
+    if !user.can_convert() {
+        return Err(ApiError::Forbidden("Missing 'convert' scope".to_string()));
+    }
+    let Json(params) = body.map_err(|e| ApiError::InvalidInput(e.body_text()))?;
+    state.job_queue.check_user_quota(&user.user_id).await?;
+
+    owned_upload(&state, &user, &upload_id).await?;
+    let _lock = state
+        .upload_sessions
+        .lock(&upload_id)
+        .ok_or_else(|| ApiError::UploadConflict("A chunk is being uploaded".to_string()))?;
+    let session = owned_upload(&state, &user, &upload_id).await?;
+    if !session.is_complete() {
+        return Err(ApiError::UploadConflict(format!(
+            "Upload incomplete: received {} of {} bytes",
+            session.offset, session.size
+        )));
+    }
+
+    let path = session_path(&state.config.upload_dir, &upload_id);
+    let head = read_head(&path)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read upload: {}", e)))?;
+    let job = prepare_job(&state, &user, session.filename, &head, session.size, params).await?;
+
+    // 先將已接收的檔案移到任務目錄再儲存任務
+    let job_id = job.job_id.clone();
+    let job_path = job_upload_path(&state, &job).await?;
+    if let Err(e) = tokio::fs::rename(&path, &job_path).await {
+        remove_job_files(&state, &job_id).await;
+        return Err(ApiError::InternalError(format!(
+            "Failed to move upload: {}",
+            e
+        )));
+    }
+
+    let result = async {
+        state.job_store.create_job(job.clone()).await?;
+        enqueue_job(&state, job).await
+    }
+    .await;
+    match result {
+        Ok(response) => {
+            state.upload_sessions.delete(&upload_id).await;
+            Ok(Json(ApiResponse::success(response)))
+        }
+        Err(e) => {
+            // 移回工作階段，稍後可再次完成
+            if let Err(e) = tokio::fs::rename(&job_path, &path).await {
+                tracing::warn!("Failed to restore upload {}: {}", upload_id, e);
+            }
+            discard_job(&state, &job_id).await;
+            Err(e)
+        }
+    }
+}
+
+/// 取得使用者自己的上傳工作階段（已過期視為不存在）
+async fn owned_upload(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    upload_id: &str,
+) -> Result<UploadSession, ApiError> {
+    let session = state
+        .upload_sessions
+        .get(upload_id)
+        .await
+        .filter(|s| s.expires_at > chrono::Utc::now().timestamp())
+        .ok_or_else(|| ApiError::UploadNotFound(upload_id.to_string()))?;
+
+    if session.user_id != user.user_id {
+        return Err(ApiError::Forbidden(
+            "Not authorized to access this upload".to_string(),
+        ));
+    }
+    Ok(session)
+}
+
+/// 預覽轉換計畫（不建立任務）
+pub async fn plan_conversion(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    body: Result<Json<ConversionPlanRequest>, JsonRejection>,
+) -> Result<Json<ApiResponse<ConversionPlanResponse>>, ApiError> {
+    let Json(request) = body.map_err(|e| ApiError::InvalidInput(e.body_text()))?;
+    let input_format = request.input_format.trim_start_matches('.').to_lowercase();
+
+    let plan = select_plan(
+        &state,
+        &input_format,
+        &request.output_format,
+        request.engine_id.as_deref(),
+        request.file_size.unwrap_or(0),
+    )
+    .await?;
+    validate_plan_options(&state, &plan, request.options.as_ref()).await?;
+    let engine_candidates = rank_engine_ids(&state, &input_format, &request.output_format).await;
+
+    Ok(Json(ApiResponse::success(ConversionPlanResponse {
+        input_format,
+        output_format: request.output_format,
+        engine_candidates,
+        steps: plan.steps,
+    })))
+}
+
+/// 選擇轉換步驟
+///
+/// 指定引擎時只使用該引擎；否則依優先順序選擇單一引擎，沒有時串接多個引擎。
+async fn select_plan(
+    state: &AppState,
+    input_format: &str,
+    output_format: &str,
+    engine_id: Option<&str>,
+    file_size: u64,
+) -> Result<ConversionPlan, ApiError> {
+    let registry = &state.engine_registry;
+    let unsupported = || ApiError::UnsupportedConversion {
+        from: input_format.to_string(),
+        to: output_format.to_string(),
+    };
+
+    if let Some(id) = engine_id {
+        // 驗證指定的引擎
+        let engine = registry
+            .get_engine(id)
+            .await
+            .ok_or_else(|| ApiError::EngineNotFound(id.to_string()))?;
+
+        if !engine.is_usable() {
+            return Err(ApiError::EngineDisabled(id.to_string()));
+        }
+
+        if !engine.supports_conversion(input_format, output_format) {
+            return Err(unsupported());
+        }
+
+        // 檢查引擎的檔案大小上限
+        if !engine.accepts_file_size(file_size) {
+            return Err(ApiError::FileTooLarge(engine.max_file_size_bytes()));
+        }
+        return Ok(ConversionPlan::single(id, input_format, output_format));
+    }
+
+    if let Some(plan) = registry
+        .plan_conversion(input_format, output_format, file_size)
+        .await
+    {
+        return Ok(plan);
+    }
+
+    // 沒有可用的計畫時，區分檔案過大與不支援的轉換
+    if let Some(limit) = registry
+        .max_file_size_for_conversion(input_format, output_format)
+        .await
+    {
+        return Err(ApiError::FileTooLarge(limit));
+    }
+    if let Some(plan) = registry
+        .plan_conversion(input_format, output_format, 0)
+        .await
+    {
+        if let Some(limit) = registry.max_file_size_for_plan(&plan).await {
+            return Err(ApiError::FileTooLarge(limit));
+        }
+    }
+    Err(unsupported())
+}
+
+/// 依各步驟引擎的參數格式驗證選項
+///
+/// 多步驟轉換時每個引擎只驗證自己宣告的選項，沒有任何步驟宣告的選項視為錯誤。
+async fn validate_plan_options(
+    state: &AppState,
+    plan: &ConversionPlan,
+    options: Option<&serde_json::Value>,
+) -> Result<(), ApiError> {
+    let mut violations = Vec::new();
+    let mut checked = Vec::new();
+    let mut declared = Vec::new();
+
+    for step in &plan.steps {
+        if checked.contains(&step.engine_id) {
+            continue;
+        }
+        checked.push(step.engine_id.clone());
+
+        let schema = state
+            .engine_registry
+            .get_engine(&step.engine_id)
+            .await
+            .and_then(|e| e.params_schema);
+        let Some(schema) = schema else {
+            continue;
+        };
+        if !plan.is_multi_step() {
+            violations.extend(validate_options(&schema, options));
+            continue;
+        }
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            declared.extend(properties.keys().cloned());
+        }
+        let scoped = declared_options(Some(&schema), options);
+        violations.extend(validate_options(&schema, scoped.as_ref()));
+    }
+
+    if plan.is_multi_step() {
+        match options {
+            Some(serde_json::Value::Object(object)) => {
+                violations.extend(
+                    object
+                        .keys()
+                        .filter(|key| !declared.contains(key))
+                        .map(|key| ParamViolation {
+                            path: format!("options.{}", key),
+                            message: "is not a recognized option".to_string(),
+                        }),
+                );
+            }
+            Some(other) if !other.is_null() => violations.push(ParamViolation {
+                path: "options".to_string(),
+                message: "expected object".to_string(),
+            }),
+            _ => {}
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidOptions(violations))
+    }
+}
+
+/// 支援指定轉換的引擎 ID，依優先順序排列
+async fn rank_engine_ids(state: &AppState, input_format: &str, output_format: &str) -> Vec<String> {
+    state
+        .engine_registry
+        .rank_engines_for_conversion(input_format, output_format)
+        .await
+        .into_iter()
+        .map(|e| e.engine_id)
+        .collect()
+}
+
+/// 轉換 multipart 讀取錯誤；超過請求大小上限時回報檔案過大
+fn multipart_error(state: &AppState, context: &str, error: MultipartError) -> ApiError {
+    if error.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::FileTooLarge(state.config.max_file_size)
+    } else {
+        ApiError::InvalidInput(format!("{}: {}", context, error))
+    }
+}
+
+/// 執行佇列派送的任務
+pub async fn run_queued_job(state: AppState, job_id: String, cancel: CancellationToken) {
+    let Some(job) = state.job_store.get_job(&job_id).await else {
+        tracing::warn!("Queued job {} no longer exists", job_id);
+        return;
+    };
+
+    if job.status != JobStatus::Pending {
+        return;
+    }
+
+    let input_path = PathBuf::from(&state.config.upload_dir)
+        .join(&job.job_id)
+        .join(job.upload_filename());
+
+    let steps = job.conversion_steps();
+    process_conversion(state, job.job_id, input_path, steps, job.options, cancel).await;
+}
+
 /// 處理轉換任務（後台執行）
 async fn process_conversion(
     state: AppState,
     job_id: String,
     input_path: PathBuf,
-    output_format: String,
-    engine_id: String,
+    steps: Vec<ConversionStep>,
     options: Option<serde_json::Value>,
+    cancel: CancellationToken,
 ) {
-    // 更新狀態為處理中
-    state.job_store.update_status(&job_id, JobStatus::Processing).await;
+    // 更新狀態為處理中（任務已被取消時不會更新）
+    if state
+        .job_store
+        .update_status(&job_id, JobStatus::Processing)
+        .await
+        .is_none()
+    {
+        return;
+    }
     state.job_store.update_progress(&job_id, 10).await;
 
     // 建立輸出目錄
@@ -258,63 +811,251 @@ async fn process_conversion(
         return;
     }
 
-    // 呼叫後端 API 進行轉換
-    let result = call_backend_convert(
-        &state,
-        &input_path,
-        &output_dir,
-        &output_format,
-        &engine_id,
-        options,
-    )
-    .await;
+    // 呼叫後端 API 進行轉換；取消時中止請求（drop future 會中斷連線）
+    let result = tokio::select! {
+        result = run_steps(&state, &job_id, &input_path, &output_dir, &steps, options) => result,
+        _ = cancel.cancelled() => {
+            tracing::info!("🛑 Job {} cancelled, removing partial output", job_id);
+            remove_dir_if_exists(&output_dir).await;
+            return;
+        }
+    };
 
     match result {
         Ok(output_file) => {
-            state.job_store.complete_job(&job_id, output_file).await;
+            // 先記錄校驗值，任務完成後即可提供 ETag（失敗時下載時補算）
+            match file_checksum(std::path::Path::new(&output_file)).await {
+                Ok(checksum) => {
+                    state.job_store.set_output_checksum(&job_id, checksum).await;
+                }
+                Err(e) => tracing::warn!("Failed to checksum output of job {}: {}", job_id, e),
+            }
+            // 轉換期間任務被取消或刪除時，不保留輸出
+            if state
+                .job_store
+                .complete_job(&job_id, output_file)
+                .await
+                .is_none()
+            {
+                remove_dir_if_exists(&output_dir).await;
+            }
         }
-        Err(e) => {
-            let _ = state.job_store.fail_job(&job_id, e.to_string()).await;
+        Err(message) => {
+            let _ = state.job_store.fail_job(&job_id, message).await;
+        }
+    }
+}
+
+/// 依序執行轉換步驟，回傳最終輸出檔案路徑
+///
+/// 中間檔案寫在任務的上傳目錄（隨上傳檔案一併清除），最後一步輸出到輸出目錄。
+/// 進度在 10 到 90 之間依步驟數平均分配，每個步驟內再依上傳與下載的 bytes 推進。
+async fn run_steps(
+    state: &AppState,
+    job_id: &str,
+    input_path: &std::path::Path,
+    output_dir: &std::path::Path,
+    steps: &[ConversionStep],
+    options: Option<serde_json::Value>,
+) -> Result<String, String> {
+    let work_dir = input_path.parent().unwrap_or(output_dir);
+    let total = steps.len();
+    let mut current = input_path.to_path_buf();
+    let step_options = scope_step_options(state, steps, options).await;
+
+    for ((index, step), options) in steps.iter().enumerate().zip(step_options) {
+        let target_dir = if index + 1 == total {
+            output_dir
+        } else {
+            work_dir
+        };
+        if total > 1 {
+            tracing::info!(
+                "🔗 Job {} step {}/{}: {} → {} ({})",
+                job_id,
+                index + 1,
+                total,
+                step.input_format,
+                step.output_format,
+                step.engine_id
+            );
+        }
+
+        let start = (10 + 80 * index / total) as u8;
+        let end = (10 + 80 * (index + 1) / total) as u8;
+        let output = call_backend_convert(
+            state,
+            job_id,
+            &current,
+            target_dir,
+            step,
+            options,
+            (start, end),
+        )
+        .await
+        .map_err(|e| {
+            if total > 1 {
+                format!(
+                    "步驟 {}/{}（{}）失敗：{}",
+                    index + 1,
+                    total,
+                    step.engine_id,
+                    e
+                )
+            } else {
+                e.to_string()
+            }
+        })?;
+        current = PathBuf::from(output);
+
+        state.job_store.update_progress(job_id, end).await;
+    }
+
+    Ok(current.to_string_lossy().to_string())
+}
+
+/// 各步驟要傳給後端的選項
+///
+/// 單一步驟原樣傳遞；多步驟時每個步驟只收到其引擎 `params_schema` 宣告的選項。
+async fn scope_step_options(
+    state: &AppState,
+    steps: &[ConversionStep],
+    options: Option<serde_json::Value>,
+) -> Vec<Option<serde_json::Value>> {
+    if steps.len() <= 1 {
+        return vec![options; steps.len()];
+    }
+    let mut scoped = Vec::with_capacity(steps.len());
+    for step in steps {
+        let schema = state
+            .engine_registry
+            .get_engine(&step.engine_id)
+            .await
+            .and_then(|e| e.params_schema);
+        scoped.push(declared_options(schema.as_ref(), options.as_ref()));
+    }
+    scoped
+}
+
+/// 依傳輸的 bytes 在指定區間內推進任務進度
+///
+/// 只在百分比變動時寫入，避免每個區塊都更新資料庫。
+#[derive(Clone)]
+struct TransferProgress {
+    state: AppState,
+    job_id: String,
+    start: u8,
+    end: u8,
+    total: u64,
+    transferred: Arc<AtomicU64>,
+    reported: Arc<AtomicU8>,
+}
+
+impl TransferProgress {
+    fn new(state: &AppState, job_id: &str, (start, end): (u8, u8), total: u64) -> Self {
+        Self {
+            state: state.clone(),
+            job_id: job_id.to_string(),
+            start,
+            end,
+            total,
+            transferred: Arc::new(AtomicU64::new(0)),
+            reported: Arc::new(AtomicU8::new(start)),
+        }
+    }
+
+    async fn advance(&self, bytes: usize) {
+        if self.total == 0 {
+            return;
+        }
+        let bytes = bytes as u64;
+        let transferred = self.transferred.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        let span = u64::from(self.end - self.start);
+        let progress = self.start + (span * transferred.min(self.total) / self.total) as u8;
+        if self.reported.fetch_max(progress, Ordering::Relaxed) < progress {
+            self.state
+                .job_store
+                .update_progress(&self.job_id, progress)
+                .await;
         }
     }
 }
 
 /// 呼叫後端轉換 API
+///
+/// 上傳檔案以串流送出，轉換結果直接串流寫入輸出檔；
+/// 進度區間的前半依上傳、後半依下載（後端有回傳 Content-Length 時）推進。
 async fn call_backend_convert(
     state: &AppState,
-    input_path: &PathBuf,
-    output_dir: &PathBuf,
-    output_format: &str,
-    _engine_id: &str,
-    _options: Option<serde_json::Value>,
+    job_id: &str,
+    input_path: &std::path::Path,
+    output_dir: &std::path::Path,
+    step: &ConversionStep,
+    options: Option<serde_json::Value>,
+    (start, end): (u8, u8),
 ) -> Result<String, ApiError> {
     let client = reqwest::Client::new();
-    
-    // 讀取檔案
-    let file_data = tokio::fs::read(input_path)
+    let ConversionStep {
+        engine_id,
+        output_format,
+        ..
+    } = step;
+    let timeout = state
+        .engine_registry
+        .get_engine(engine_id)
+        .await
+        .and_then(|e| e.timeout_secs)
+        .unwrap_or(DEFAULT_CONVERT_TIMEOUT_SECS);
+    let middle = start + (end - start) / 2;
+
+    // 開啟輸入檔案
+    let file = File::open(input_path)
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to read input file: {}", e)))?;
+    let file_size = file
+        .metadata()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read input file: {}", e)))?
+        .len();
 
     let filename = input_path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("file");
 
-    // 建立 multipart 表單
-    let form = reqwest::multipart::Form::new()
+    let upload_progress = TransferProgress::new(state, job_id, (start, middle), file_size);
+    let body = ReaderStream::new(file).then(move |chunk| {
+        let progress = upload_progress.clone();
+        async move {
+            if let Ok(bytes) = &chunk {
+                progress.advance(bytes.len()).await;
+            }
+            chunk
+        }
+    });
+
+    // 建立 multipart 表單；選項已依引擎的 params_schema 驗證過，以 JSON 字串傳遞
+    let mut form = reqwest::multipart::Form::new()
         .part(
             "file",
-            reqwest::multipart::Part::bytes(file_data)
-                .file_name(filename.to_string()),
+            reqwest::multipart::Part::stream_with_length(
+                reqwest::Body::wrap_stream(body),
+                file_size,
+            )
+            .file_name(filename.to_string()),
         )
-        .text("targetFormat", output_format.to_string());
+        .text("targetFormat", output_format.to_string())
+        .text("engine", engine_id.to_string());
+    if let Some(options) = options {
+        form = form.text("options", options.to_string());
+    }
 
     // 呼叫後端 API
     let url = format!("{}/api/convert", state.config.backend_url);
     let response = client
         .post(&url)
         .multipart(form)
-        .timeout(std::time::Duration::from_secs(300))
+        .timeout(std::time::Duration::from_secs(timeout))
         .send()
         .await
         .map_err(|e| ApiError::BackendError(format!("Backend request failed: {}", e)))?;
@@ -328,12 +1069,6 @@ async fn call_backend_convert(
         )));
     }
 
-    // 取得轉換後的檔案
-    let content = response
-        .bytes()
-        .await
-        .map_err(|e| ApiError::BackendError(format!("Failed to read response: {}", e)))?;
-
     // 產生輸出檔名
     let stem = input_path
         .file_stem()
@@ -342,12 +1077,91 @@ async fn call_backend_convert(
     let output_filename = format!("{}.{}", stem, output_format);
     let output_path = output_dir.join(&output_filename);
 
-    // 寫入輸出檔案
-    tokio::fs::write(&output_path, &content)
+    // 將轉換結果寫入輸出檔案，失敗時不留下不完整的檔案
+    let download_progress = TransferProgress::new(
+        state,
+        job_id,
+        (middle, end),
+        response.content_length().unwrap_or(0),
+    );
+    if let Err(e) = write_response(response, &output_path, &download_progress).await {
+        let _ = tokio::fs::remove_file(&output_path).await;
+        return Err(e);
+    }
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// 將後端回應逐塊寫入檔案
+async fn write_response(
+    response: reqwest::Response,
+    output_path: &std::path::Path,
+    progress: &TransferProgress,
+) -> Result<(), ApiError> {
+    let mut file = File::create(output_path)
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to write output file: {}", e)))?;
 
-    Ok(output_path.to_string_lossy().to_string())
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk =
+            chunk.map_err(|e| ApiError::BackendError(format!("Failed to read response: {}", e)))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to write output file: {}", e)))?;
+        progress.advance(chunk.len()).await;
+    }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to write output file: {}", e)))
+}
+
+/// 列出使用者的任務
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    query: Result<Query<JobListQuery>, QueryRejection>,
+) -> Result<Json<ApiResponse<JobListResponse>>, ApiError> {
+    let Query(query) = query.map_err(|e| ApiError::InvalidInput(e.body_text()))?;
+
+    let after = query
+        .cursor
+        .as_deref()
+        .map(str::parse::<JobCursor>)
+        .transpose()
+        .map_err(ApiError::InvalidInput)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let filter = JobFilter {
+        status: query.status,
+        engine_id: query.engine_id,
+        output_format: query.output_format,
+        created_after: query.created_after,
+        created_before: query.created_before,
+    };
+
+    let page = state
+        .job_store
+        .list_user_jobs(&user.user_id, filter, query.order, after, limit)
+        .await;
+
+    let mut jobs = Vec::with_capacity(page.jobs.len());
+    for job in &page.jobs {
+        let mut response = JobStatusResponse::from(job);
+        if job.status == JobStatus::Pending {
+            response.queue_position = state.job_queue.position(&job.job_id).await;
+        }
+        jobs.push(response);
+    }
+
+    Ok(Json(ApiResponse::success(JobListResponse {
+        jobs,
+        total: page.total,
+        next_cursor: page.next_cursor.map(|c| c.to_string()),
+    })))
 }
 
 /// 取得任務狀態
@@ -368,15 +1182,118 @@ pub async fn get_job_status(
         return Err(ApiError::Forbidden("Not authorized to access this job".to_string()));
     }
 
+    let mut response = JobStatusResponse::from(&job);
+    response.queue_position = state.job_queue.position(&job_id).await;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 取消任務
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(job_id): Path<String>,
+) -> Result<Json<ApiResponse<JobStatusResponse>>, ApiError> {
+    let job = request_cancellation(&state, &user.user_id, &job_id).await?;
     Ok(Json(ApiResponse::success(JobStatusResponse::from(&job))))
 }
 
+/// 取消使用者自己的任務（REST 與 GraphQL 共用）
+///
+/// 先在資料庫標記為已取消，再通知佇列移除或中止執行中的請求。
+pub async fn request_cancellation(
+    state: &AppState,
+    user_id: &str,
+    job_id: &str,
+) -> Result<Job, ApiError> {
+    let job = state
+        .job_store
+        .get_job(job_id)
+        .await
+        .ok_or_else(|| ApiError::JobNotFound(job_id.to_string()))?;
+
+    if job.user_id != user_id {
+        return Err(ApiError::Forbidden("Not authorized to access this job".to_string()));
+    }
+
+    let job = state
+        .job_store
+        .cancel_job(job_id)
+        .await
+        .ok_or_else(|| ApiError::JobAlreadyFinished(job_id.to_string()))?;
+
+    state.job_queue.cancel(job_id).await;
+    Ok(job)
+}
+
+/// 刪除任務
+pub async fn delete_job(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(job_id): Path<String>,
+) -> Result<Json<ApiResponse<DeleteJobResponse>>, ApiError> {
+    let job = request_deletion(&state, &user.user_id, &job_id).await?;
+    Ok(Json(ApiResponse::success(DeleteJobResponse {
+        job_id: job.job_id,
+        message: "Job deleted".to_string(),
+    })))
+}
+
+/// 刪除使用者自己的任務及其檔案（REST 與 GraphQL 共用）
+///
+/// 進行中的任務會先從佇列移除或中止，再刪除資料庫記錄與上傳、輸出目錄。
+pub async fn request_deletion(
+    state: &AppState,
+    user_id: &str,
+    job_id: &str,
+) -> Result<Job, ApiError> {
+    let job = state
+        .job_store
+        .get_job(job_id)
+        .await
+        .ok_or_else(|| ApiError::JobNotFound(job_id.to_string()))?;
+
+    if job.user_id != user_id {
+        return Err(ApiError::Forbidden("Not authorized to access this job".to_string()));
+    }
+
+    state.job_queue.cancel(job_id).await;
+
+    let job = state
+        .job_store
+        .delete_job(job_id)
+        .await
+        .ok_or_else(|| ApiError::JobNotFound(job_id.to_string()))?;
+
+    remove_job_files(state, job_id).await;
+    Ok(job)
+}
+
+/// 移除任務的上傳與輸出目錄（含下載時產生的 ZIP）
+pub async fn remove_job_files(state: &AppState, job_id: &str) {
+    remove_dir_if_exists(&PathBuf::from(&state.config.upload_dir).join(job_id)).await;
+    remove_dir_if_exists(&PathBuf::from(&state.config.output_dir).join(job_id)).await;
+}
+
+/// 移除目錄；不存在時忽略，其他錯誤記錄後忽略
+async fn remove_dir_if_exists(dir: &std::path::Path) {
+    match tokio::fs::remove_dir_all(dir).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Failed to remove {}: {}", dir.display(), e),
+    }
+}
+
 /// 下載轉換結果
 pub async fn download_job_result(
     State(state): State<AppState>,
     user: AuthenticatedUser,
     Path(job_id): Path<String>,
+    query: Result<Query<DownloadQuery>, QueryRejection>,
+    headers: HeaderMap,
 ) -> Result<Response, ApiError> {
+    let Query(query) = query.map_err(|e| ApiError::InvalidInput(e.body_text()))?;
+
     // 檢查下載權限
     if !user.can_download() {
         return Err(ApiError::Forbidden("Missing 'download' scope".to_string()));
@@ -404,64 +1321,55 @@ pub async fn download_job_result(
         .ok_or_else(|| ApiError::InternalError("Output file not found".to_string()))?;
 
     let output_path = PathBuf::from(&output_file);
-    
-    // 建立 ZIP 檔案
-    let zip_filename = format!("{}.zip", job_id);
-    let zip_path = output_path
-        .parent()
-        .unwrap_or(&output_path)
-        .join(&zip_filename);
-
-    // 建立 ZIP
-    create_zip_file(&output_path, &zip_path)?;
-
-    // 讀取 ZIP 檔案
-    let file = File::open(&zip_path)
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to open zip file: {}", e)))?;
-
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
-
-    // 回傳檔案
-    let response = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "application/zip")
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", zip_filename),
-        )
-        .body(body)
-        .map_err(|e| ApiError::InternalError(format!("Failed to build response: {}", e)))?;
-
-    Ok(response)
-}
-
-/// 建立 ZIP 檔案
-fn create_zip_file(source: &PathBuf, zip_path: &PathBuf) -> Result<(), ApiError> {
-    let zip_file = std::fs::File::create(zip_path)
-        .map_err(|e| ApiError::InternalError(format!("Failed to create zip: {}", e)))?;
-
-    let mut zip = ZipWriter::new(zip_file);
-    let options = SimpleFileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated);
-
-    let filename = source
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("output");
-
-    zip.start_file(filename, options)
-        .map_err(|e| ApiError::InternalError(format!("Failed to start zip entry: {}", e)))?;
 
-    let content = std::fs::read(source)
-        .map_err(|e| ApiError::InternalError(format!("Failed to read source file: {}", e)))?;
-
-    std::io::Write::write_all(&mut zip, &content)
-        .map_err(|e| ApiError::InternalError(format!("Failed to write to zip: {}", e)))?;
+    // 舊任務沒有記錄校驗值時補算
+    let checksum = match job.output_checksum {
+        Some(checksum) => checksum,
+        None => {
+            let checksum = file_checksum(&output_path).await?;
+            state
+                .job_store
+                .set_output_checksum(&job_id, checksum.clone())
+                .await;
+            checksum
+        }
+    };
 
-    zip.finish()
-        .map_err(|e| ApiError::InternalError(format!("Failed to finish zip: {}", e)))?;
+    let filename = download_filename(&output_path, &job.output_format)
+        .unwrap_or_else(|| format!("{}.{}", job_id, base_format(&job.output_format)));
 
-    Ok(())
+    match query.format {
+        DownloadFormat::Raw => {
+            let validators = Validators::new(&checksum, job.completed_at);
+            let content_type = content_type(&job.output_format);
+            file_response(
+                &output_path,
+                &content_type,
+                &filename,
+                &validators,
+                &headers,
+            )
+            .await
+        }
+        DownloadFormat::Zip => {
+            let zip_filename = format!("{}.zip", job_id);
+            let zip_path = output_path
+                .parent()
+                .unwrap_or(&output_path)
+                .join(&zip_filename);
+            // 沿用已建立的 ZIP，續傳時內容不變
+            if !tokio::fs::try_exists(&zip_path).await.unwrap_or(false) {
+                create_zip_file(&output_path, &zip_path, &filename, job.completed_at).await?;
+            }
+            let validators = Validators::new(&format!("{}-zip", checksum), job.completed_at);
+            file_response(
+                &zip_path,
+                "application/zip",
+                &zip_filename,
+                &validators,
+                &headers,
+            )
+            .await
+        }
+    }
 }
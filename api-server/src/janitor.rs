@@ -0,0 +1,186 @@
+//! 保留期限清理模組
+//!
+//! 定期刪除超過保留期限的任務，連同其上傳與輸出目錄：
+//! - 已完成與失敗（含已取消）的任務使用不同的保留期限
+//! - 等待中與處理中的任務不會被清理
+//! - 刪除閒置過期的可續傳上傳工作階段與其已接收的內容
+//! - 記錄每次清理釋放的磁碟空間，啟動以來的累計值由健康檢查回報
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::auth::AppState;
+use crate::handlers::remove_job_files;
+use crate::resumable::session_path;
+
+/// 保留期限設定
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// 已完成任務的保留期限
+    pub completed_ttl: Duration,
+    /// 失敗與已取消任務的保留期限
+    pub failed_ttl: Duration,
+    /// 清理間隔（0 表示停用定期清理）
+    pub interval: Duration,
+}
+
+/// 清理結果
+#[derive(Debug, Default)]
+pub struct SweepReport {
+    /// 已刪除的任務
+    pub removed: Vec<String>,
+    /// 已過期的上傳工作階段
+    pub expired_uploads: Vec<String>,
+    /// 釋放的磁碟空間（bytes）
+    pub reclaimed_bytes: u64,
+}
+
+/// 啟動以來的清理累計（可複製，共用同一組計數）
+#[derive(Debug, Clone, Default)]
+pub struct RetentionStats {
+    removed_jobs: Arc<AtomicU64>,
+    expired_uploads: Arc<AtomicU64>,
+    reclaimed_bytes: Arc<AtomicU64>,
+}
+
+/// 清理累計的快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RetentionTotals {
+    /// 已刪除的任務數
+    pub removed_jobs: u64,
+    /// 已過期的上傳工作階段數
+    pub expired_uploads: u64,
+    /// 釋放的磁碟空間（bytes）
+    pub reclaimed_bytes: u64,
+}
+
+impl RetentionStats {
+    /// 累加一次清理的結果
+    pub fn record(&self, report: &SweepReport) {
+        self.removed_jobs
+            .fetch_add(report.removed.len() as u64, Ordering::Relaxed);
+        self.expired_uploads
+            .fetch_add(report.expired_uploads.len() as u64, Ordering::Relaxed);
+        self.reclaimed_bytes
+            .fetch_add(report.reclaimed_bytes, Ordering::Relaxed);
+    }
+
+    /// 目前的累計值
+    pub fn totals(&self) -> RetentionTotals {
+        RetentionTotals {
+            removed_jobs: self.removed_jobs.load(Ordering::Relaxed),
+            expired_uploads: self.expired_uploads.load(Ordering::Relaxed),
+            reclaimed_bytes: self.reclaimed_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 執行一次清理，結果同時累加到 `state.retention_stats`
+pub async fn sweep(state: &AppState, policy: &RetentionPolicy) -> SweepReport {
+    let mut report = SweepReport::default();
+    let now = chrono::Utc::now().timestamp();
+    let completed_before = now - policy.completed_ttl.as_secs() as i64;
+    let failed_before = now - policy.failed_ttl.as_secs() as i64;
+
+    let jobs = state
+        .job_store
+        .get_expired_jobs(completed_before, failed_before)
+        .await;
+
+    for job in jobs {
+        let bytes = job_disk_usage(state, &job.job_id).await;
+
+        // 清理期間被使用者刪除的任務跳過
+        if state.job_store.delete_job(&job.job_id).await.is_none() {
+            continue;
+        }
+        remove_job_files(state, &job.job_id).await;
+
+        report.reclaimed_bytes += bytes;
+        report.removed.push(job.job_id);
+    }
+
+    for session in state.upload_sessions.take_expired(now).await {
+        let path = session_path(&state.config.upload_dir, &session.upload_id);
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            report.reclaimed_bytes += metadata.len();
+        }
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+        report.expired_uploads.push(session.upload_id);
+    }
+
+    if !report.removed.is_empty() || !report.expired_uploads.is_empty() {
+        state.retention_stats.record(&report);
+        let totals = state.retention_stats.totals();
+        info!(
+            "🧹 Retention sweep: {} jobs expired, {} uploads expired, {} bytes reclaimed \
+             (since startup: {} jobs, {} uploads, {} bytes)",
+            report.removed.len(),
+            report.expired_uploads.len(),
+            report.reclaimed_bytes,
+            totals.removed_jobs,
+            totals.expired_uploads,
+            totals.reclaimed_bytes
+        );
+    }
+
+    report
+}
+
+/// 啟動定期清理；清理間隔為 0 時不啟動
+pub fn start(state: AppState, policy: RetentionPolicy) -> Option<JoinHandle<()>> {
+    if policy.interval.is_zero() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(policy.interval);
+
+        loop {
+            ticker.tick().await;
+            sweep(&state, &policy).await;
+        }
+    }))
+}
+
+/// 任務上傳與輸出目錄佔用的空間
+async fn job_disk_usage(state: &AppState, job_id: &str) -> u64 {
+    let dirs = [
+        PathBuf::from(&state.config.upload_dir).join(job_id),
+        PathBuf::from(&state.config.output_dir).join(job_id),
+    ];
+
+    tokio::task::spawn_blocking(move || dirs.iter().map(|dir| dir_size(dir)).sum())
+        .await
+        .unwrap_or(0)
+}
+
+/// 遞迴計算目錄大小；無法讀取的項目略過
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return 0,
+        Err(e) => {
+            warn!("Failed to read {}: {}", path.display(), e);
+            return 0;
+        }
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
@@ -1,125 +1,455 @@
 //! 任務管理模組
+//!
+//! 任務持久化於 SQLite，伺服器重啟後仍可查詢任務狀態。
 
-use crate::models::{Job, JobStatus};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::db::Database;
+use crate::error::ApiResult;
+use crate::models::{Job, JobPriority, JobStatus, SortOrder};
+
+/// 查詢任務時使用的欄位（順序需與 `job_from_row` 一致）
+const JOB_COLUMNS: &str = "job_id, user_id, original_filename, input_format, output_format, \
+     engine_id, status, progress, error_message, output_file, created_at, updated_at, completed_at, \
+     options, priority, engine_candidates, steps, warnings, output_checksum";
+
+/// 取消任務時記錄的訊息
+pub const CANCELLED_MESSAGE: &str = "已取消";
+
+/// 任務列表篩選條件
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    /// 任務狀態
+    pub status: Option<JobStatus>,
+    /// 引擎 ID
+    pub engine_id: Option<String>,
+    /// 輸出格式
+    pub output_format: Option<String>,
+    /// 建立時間下限（含）
+    pub created_after: Option<i64>,
+    /// 建立時間上限（不含）
+    pub created_before: Option<i64>,
+}
+
+/// 分頁游標：上一頁最後一筆任務的建立時間與 ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobCursor {
+    pub created_at: i64,
+    pub job_id: String,
+}
+
+impl JobCursor {
+    /// 以任務建立游標
+    pub fn from_job(job: &Job) -> Self {
+        Self {
+            created_at: job.created_at,
+            job_id: job.job_id.clone(),
+        }
+    }
+}
+
+impl fmt::Display for JobCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.created_at, self.job_id)
+    }
+}
+
+impl FromStr for JobCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (created_at, job_id) = s
+            .split_once(':')
+            .filter(|(_, id)| !id.is_empty())
+            .ok_or_else(|| format!("Invalid cursor: {}", s))?;
+        let created_at = created_at
+            .parse()
+            .map_err(|_| format!("Invalid cursor: {}", s))?;
+        Ok(Self {
+            created_at,
+            job_id: job_id.to_string(),
+        })
+    }
+}
+
+/// 任務列表的一頁
+#[derive(Debug, Default)]
+pub struct JobPage {
+    /// 本頁任務
+    pub jobs: Vec<Job>,
+    /// 符合篩選條件的任務總數
+    pub total: usize,
+    /// 下一頁游標
+    pub next_cursor: Option<JobCursor>,
+}
 
 /// 任務儲存器
 #[derive(Clone)]
 pub struct JobStore {
-    jobs: Arc<RwLock<HashMap<String, Job>>>,
+    db: Database,
 }
 
 impl JobStore {
-    /// 建立新的任務儲存器
+    /// 建立新的任務儲存器（記憶體資料庫）
     pub fn new() -> Self {
-        Self {
-            jobs: Arc::new(RwLock::new(HashMap::new())),
-        }
+        Self::with_database(Database::in_memory())
+    }
+
+    /// 開啟指定路徑的任務資料庫
+    pub fn open(path: &str) -> ApiResult<Self> {
+        Ok(Self::with_database(Database::open(path)?))
+    }
+
+    /// 使用既有的資料庫連線
+    pub fn with_database(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// 底層資料庫
+    pub fn database(&self) -> &Database {
+        &self.db
     }
 
     /// 建立新任務
-    pub async fn create_job(&self, job: Job) -> Job {
-        let mut jobs = self.jobs.write().await;
-        let job_id = job.job_id.clone();
-        jobs.insert(job_id, job.clone());
-        job
+    pub async fn create_job(&self, job: Job) -> ApiResult<Job> {
+        let row = job.clone();
+        self.db
+            .call(move |conn| {
+                conn.execute(
+                    &format!(
+                        "INSERT INTO jobs ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+                        JOB_COLUMNS
+                    ),
+                    params![
+                        row.job_id,
+                        row.user_id,
+                        row.original_filename,
+                        row.input_format,
+                        row.output_format,
+                        row.engine_id,
+                        row.status.to_string(),
+                        row.progress,
+                        row.error_message,
+                        row.output_file,
+                        row.created_at,
+                        row.updated_at,
+                        row.completed_at,
+                        row.options.as_ref().map(|o| o.to_string()),
+                        row.priority.to_string(),
+                        serde_json::to_string(&row.engine_candidates).unwrap_or_default(),
+                        serde_json::to_string(&row.steps).unwrap_or_default(),
+                        serde_json::to_string(&row.warnings).unwrap_or_default(),
+                        row.output_checksum,
+                    ],
+                )
+            })
+            .await?;
+        Ok(job)
     }
 
     /// 取得任務
     pub async fn get_job(&self, job_id: &str) -> Option<Job> {
-        let jobs = self.jobs.read().await;
-        jobs.get(job_id).cloned()
+        let job_id = job_id.to_string();
+        self.query(move |conn| select_job(conn, &job_id))
+            .await
+            .flatten()
     }
 
     /// 取得使用者的所有任務
     pub async fn get_user_jobs(&self, user_id: &str) -> Vec<Job> {
-        let jobs = self.jobs.read().await;
-        jobs.values()
-            .filter(|j| j.user_id == user_id)
-            .cloned()
-            .collect()
+        let user_id = user_id.to_string();
+        self.query(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM jobs WHERE user_id = ?1 ORDER BY created_at DESC, job_id DESC",
+                JOB_COLUMNS
+            ))?;
+            let jobs = stmt
+                .query_map(params![user_id], job_from_row)?
+                .collect::<rusqlite::Result<Vec<Job>>>()?;
+            Ok(jobs)
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// 分頁列出使用者的任務
+    ///
+    /// 依建立時間（相同時依任務 ID）排序，從 `after` 游標之後開始取 `limit` 筆。
+    pub async fn list_user_jobs(
+        &self,
+        user_id: &str,
+        filter: JobFilter,
+        order: SortOrder,
+        after: Option<JobCursor>,
+        limit: usize,
+    ) -> JobPage {
+        let user_id = user_id.to_string();
+        self.query(move |conn| {
+            let mut conditions = vec!["user_id = ?".to_string()];
+            let mut values = vec![Value::from(user_id)];
+
+            if let Some(status) = filter.status {
+                conditions.push("status = ?".to_string());
+                values.push(Value::from(status.to_string()));
+            }
+            // 任務保留用戶端送出的大小寫，比對時兩邊都轉為小寫
+            if let Some(engine_id) = filter.engine_id {
+                conditions.push("lower(engine_id) = ?".to_string());
+                values.push(Value::from(engine_id.to_lowercase()));
+            }
+            if let Some(output_format) = filter.output_format {
+                conditions.push("lower(output_format) = ?".to_string());
+                values.push(Value::from(output_format.to_lowercase()));
+            }
+            if let Some(created_after) = filter.created_after {
+                conditions.push("created_at >= ?".to_string());
+                values.push(Value::from(created_after));
+            }
+            if let Some(created_before) = filter.created_before {
+                conditions.push("created_at < ?".to_string());
+                values.push(Value::from(created_before));
+            }
+
+            let total: i64 = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM jobs WHERE {}",
+                    conditions.join(" AND ")
+                ),
+                rusqlite::params_from_iter(values.iter()),
+                |row| row.get(0),
+            )?;
+
+            let (cmp, direction) = match order {
+                SortOrder::Asc => (">", "ASC"),
+                SortOrder::Desc => ("<", "DESC"),
+            };
+            if let Some(cursor) = after {
+                conditions.push(format!(
+                    "(created_at {cmp} ? OR (created_at = ? AND job_id {cmp} ?))"
+                ));
+                values.push(Value::from(cursor.created_at));
+                values.push(Value::from(cursor.created_at));
+                values.push(Value::from(cursor.job_id));
+            }
+            // 多取一筆判斷是否有下一頁
+            values.push(Value::from(limit as i64 + 1));
+
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM jobs WHERE {} ORDER BY created_at {dir}, job_id {dir} LIMIT ?",
+                JOB_COLUMNS,
+                conditions.join(" AND "),
+                dir = direction
+            ))?;
+            let mut jobs = stmt
+                .query_map(rusqlite::params_from_iter(values.iter()), job_from_row)?
+                .collect::<rusqlite::Result<Vec<Job>>>()?;
+
+            let next_cursor = if jobs.len() > limit {
+                jobs.truncate(limit);
+                jobs.last().map(JobCursor::from_job)
+            } else {
+                None
+            };
+
+            Ok(JobPage {
+                jobs,
+                total: total as usize,
+                next_cursor,
+            })
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// 取得指定狀態的所有任務（依建立時間排序）
+    pub async fn get_jobs_by_status(&self, statuses: &[JobStatus]) -> Vec<Job> {
+        let statuses: Vec<String> = statuses.iter().map(|s| s.to_string()).collect();
+        self.query(move |conn| {
+            let placeholders = vec!["?"; statuses.len()].join(", ");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM jobs WHERE status IN ({}) ORDER BY created_at ASC, job_id ASC",
+                JOB_COLUMNS, placeholders
+            ))?;
+            let jobs = stmt
+                .query_map(rusqlite::params_from_iter(statuses.iter()), job_from_row)?
+                .collect::<rusqlite::Result<Vec<Job>>>()?;
+            Ok(jobs)
+        })
+        .await
+        .unwrap_or_default()
     }
 
     /// 更新任務狀態
     pub async fn update_status(&self, job_id: &str, status: JobStatus) -> Option<Job> {
-        let mut jobs = self.jobs.write().await;
-        if let Some(job) = jobs.get_mut(job_id) {
-            job.status = status;
-            job.updated_at = chrono::Utc::now().timestamp();
-            if status == JobStatus::Completed {
-                job.completed_at = Some(job.updated_at);
-            }
-            Some(job.clone())
-        } else {
-            None
-        }
+        self.update(job_id, move |conn, job_id, now| {
+            let completed_at = status.is_finished().then_some(now);
+            conn.execute(
+                "UPDATE jobs SET status = ?2, updated_at = ?3, \
+                 completed_at = COALESCE(?4, completed_at) \
+                 WHERE job_id = ?1 AND status != 'cancelled'",
+                params![job_id, status.to_string(), now, completed_at],
+            )
+        })
+        .await
     }
 
     /// 更新任務進度
     pub async fn update_progress(&self, job_id: &str, progress: u8) -> Option<Job> {
-        let mut jobs = self.jobs.write().await;
-        if let Some(job) = jobs.get_mut(job_id) {
-            job.progress = progress.min(100);
-            job.updated_at = chrono::Utc::now().timestamp();
-            Some(job.clone())
-        } else {
-            None
-        }
+        let progress = progress.min(100);
+        self.update(job_id, move |conn, job_id, now| {
+            conn.execute(
+                "UPDATE jobs SET progress = ?2, updated_at = ?3 \
+                 WHERE job_id = ?1 AND status != 'cancelled'",
+                params![job_id, progress, now],
+            )
+        })
+        .await
     }
 
     /// 設定任務完成
     pub async fn complete_job(&self, job_id: &str, output_file: String) -> Option<Job> {
-        let mut jobs = self.jobs.write().await;
-        if let Some(job) = jobs.get_mut(job_id) {
-            job.status = JobStatus::Completed;
-            job.progress = 100;
-            job.output_file = Some(output_file);
-            job.updated_at = chrono::Utc::now().timestamp();
-            job.completed_at = Some(job.updated_at);
-            Some(job.clone())
-        } else {
-            None
-        }
+        self.update(job_id, move |conn, job_id, now| {
+            conn.execute(
+                "UPDATE jobs SET status = ?2, progress = 100, output_file = ?3, \
+                 updated_at = ?4, completed_at = ?4 \
+                 WHERE job_id = ?1 AND status != 'cancelled'",
+                params![job_id, JobStatus::Completed.to_string(), output_file, now],
+            )
+        })
+        .await
+    }
+
+    /// 記錄輸出檔案的 SHA-256
+    pub async fn set_output_checksum(&self, job_id: &str, checksum: String) -> Option<Job> {
+        let job_id = job_id.to_string();
+        self.query(move |conn| {
+            conn.execute(
+                "UPDATE jobs SET output_checksum = ?2 WHERE job_id = ?1",
+                params![job_id, checksum],
+            )?;
+            select_job(conn, &job_id)
+        })
+        .await
+        .flatten()
     }
 
     /// 設定任務失敗
     pub async fn fail_job(&self, job_id: &str, error_message: String) -> Option<Job> {
-        let mut jobs = self.jobs.write().await;
-        if let Some(job) = jobs.get_mut(job_id) {
-            job.status = JobStatus::Failed;
-            job.error_message = Some(error_message);
-            job.updated_at = chrono::Utc::now().timestamp();
-            Some(job.clone())
-        } else {
-            None
-        }
+        self.update(job_id, move |conn, job_id, now| {
+            conn.execute(
+                "UPDATE jobs SET status = ?2, error_message = ?3, \
+                 updated_at = ?4, completed_at = ?4 \
+                 WHERE job_id = ?1 AND status != 'cancelled'",
+                params![job_id, JobStatus::Failed.to_string(), error_message, now],
+            )
+        })
+        .await
+    }
+
+    /// 取消任務（僅限等待中或處理中的任務）
+    ///
+    /// 取消後的任務不會再被狀態、進度、完成或失敗更新覆寫。
+    pub async fn cancel_job(&self, job_id: &str) -> Option<Job> {
+        self.update(job_id, move |conn, job_id, now| {
+            conn.execute(
+                "UPDATE jobs SET status = ?2, error_message = ?3, \
+                 updated_at = ?4, completed_at = ?4 \
+                 WHERE job_id = ?1 AND status IN ('pending', 'processing')",
+                params![
+                    job_id,
+                    JobStatus::Cancelled.to_string(),
+                    CANCELLED_MESSAGE,
+                    now
+                ],
+            )
+        })
+        .await
+    }
+
+    /// 刪除任務，回傳被刪除的任務；任務不存在時回傳 None
+    pub async fn delete_job(&self, job_id: &str) -> Option<Job> {
+        let job_id = job_id.to_string();
+        self.query(move |conn| {
+            let tx = conn.transaction()?;
+            let job = select_job(&tx, &job_id)?;
+            if job.is_some() {
+                tx.execute("DELETE FROM jobs WHERE job_id = ?1", params![job_id])?;
+            }
+            tx.commit()?;
+            Ok(job)
+        })
+        .await
+        .flatten()
     }
 
     /// 檢查任務是否屬於使用者
     pub async fn is_job_owner(&self, job_id: &str, user_id: &str) -> bool {
-        let jobs = self.jobs.read().await;
-        jobs.get(job_id).map(|j| j.user_id == user_id).unwrap_or(false)
-    }
-
-    /// 清理過期任務（超過指定小時數）
-    pub async fn cleanup_old_jobs(&self, hours: i64) -> usize {
-        let mut jobs = self.jobs.write().await;
-        let now = chrono::Utc::now().timestamp();
-        let cutoff = now - (hours * 3600);
-        
-        let old_jobs: Vec<String> = jobs
-            .iter()
-            .filter(|(_, j)| j.created_at < cutoff)
-            .map(|(id, _)| id.clone())
-            .collect();
-        
-        let count = old_jobs.len();
-        for job_id in old_jobs {
-            jobs.remove(&job_id);
+        self.get_job(job_id)
+            .await
+            .map(|j| j.user_id == user_id)
+            .unwrap_or(false)
+    }
+
+    /// 取得已過保留期限的任務
+    ///
+    /// 已完成的任務在 `completed_before` 之前結束即過期；
+    /// 失敗與已取消的任務以 `failed_before` 為準。等待中與處理中的任務永不過期。
+    /// 以結束時間（`completed_at`）計算，結束後的進度或狀態寫入不會延長保留期限；
+    /// 舊版本留下、沒有結束時間的失敗任務才以 `updated_at` 代替。
+    pub async fn get_expired_jobs(&self, completed_before: i64, failed_before: i64) -> Vec<Job> {
+        self.query(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM jobs \
+                 WHERE (status = 'completed' AND COALESCE(completed_at, updated_at) <= ?1) \
+                    OR (status IN ('failed', 'cancelled') AND COALESCE(completed_at, updated_at) <= ?2) \
+                 ORDER BY COALESCE(completed_at, updated_at) ASC, job_id ASC",
+                JOB_COLUMNS
+            ))?;
+            let jobs = stmt
+                .query_map(params![completed_before, failed_before], job_from_row)?
+                .collect::<rusqlite::Result<Vec<Job>>>()?;
+            Ok(jobs)
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// 執行查詢，失敗時記錄錯誤並回傳 None
+    async fn query<F, T>(&self, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        match self.db.call(f).await {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::error!("Job store query failed: {}", e);
+                None
+            }
         }
-        count
+    }
+
+    /// 執行更新並回傳更新後的任務；任務不存在（或已取消而未更新）時回傳 None
+    async fn update<F>(&self, job_id: &str, f: F) -> Option<Job>
+    where
+        F: FnOnce(&Connection, &str, i64) -> rusqlite::Result<usize> + Send + 'static,
+    {
+        let job_id = job_id.to_string();
+        self.query(move |conn| {
+            let now = chrono::Utc::now().timestamp();
+            let tx = conn.transaction()?;
+            if f(&tx, &job_id, now)? == 0 {
+                return Ok(None);
+            }
+            let job = select_job(&tx, &job_id)?;
+            tx.commit()?;
+            Ok(job)
+        })
+        .await
+        .flatten()
     }
 }
 
@@ -128,3 +458,66 @@ impl Default for JobStore {
         Self::new()
     }
 }
+
+/// 依 ID 查詢單一任務
+fn select_job(conn: &Connection, job_id: &str) -> rusqlite::Result<Option<Job>> {
+    conn.query_row(
+        &format!("SELECT {} FROM jobs WHERE job_id = ?1", JOB_COLUMNS),
+        params![job_id],
+        job_from_row,
+    )
+    .optional()
+}
+
+/// 將資料列轉換為任務
+fn job_from_row(row: &Row<'_>) -> rusqlite::Result<Job> {
+    let status: String = row.get(6)?;
+    let status = status.parse::<JobStatus>().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, e.into())
+    })?;
+    let options: Option<String> = row.get(13)?;
+    let options = options
+        .map(|o| serde_json::from_str(&o))
+        .transpose()
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(13, rusqlite::types::Type::Text, e.into())
+        })?;
+    let priority: String = row.get(14)?;
+    let priority = priority.parse::<JobPriority>().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(14, rusqlite::types::Type::Text, e.into())
+    })?;
+    let engine_candidates: String = row.get(15)?;
+    let engine_candidates = serde_json::from_str(&engine_candidates).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(15, rusqlite::types::Type::Text, e.into())
+    })?;
+    let steps: String = row.get(16)?;
+    let steps = serde_json::from_str(&steps).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(16, rusqlite::types::Type::Text, e.into())
+    })?;
+    let warnings: String = row.get(17)?;
+    let warnings = serde_json::from_str(&warnings).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(17, rusqlite::types::Type::Text, e.into())
+    })?;
+
+    Ok(Job {
+        job_id: row.get(0)?,
+        user_id: row.get(1)?,
+        original_filename: row.get(2)?,
+        input_format: row.get(3)?,
+        output_format: row.get(4)?,
+        engine_id: row.get(5)?,
+        status,
+        progress: row.get(7)?,
+        error_message: row.get(8)?,
+        output_file: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+        completed_at: row.get(12)?,
+        options,
+        priority,
+        engine_candidates,
+        steps,
+        warnings,
+        output_checksum: row.get(18)?,
+    })
+}
@@ -21,13 +21,26 @@
 #![allow(dead_code)]
 
 pub mod auth;
+pub mod catalog;
 pub mod config;
+pub mod db;
+pub mod download;
 pub mod engine;
 pub mod error;
 pub mod graphql;
 pub mod handlers;
+pub mod janitor;
 pub mod job;
 pub mod models;
+pub mod params;
+pub mod planner;
+pub mod queue;
+pub mod recovery;
+pub mod resumable;
+pub mod router;
+pub mod sniff;
+pub mod sync;
+pub mod upload;
 
 // Re-export commonly used types
 pub use auth::{AppState, AuthenticatedUser, JwtClaims, JwtValidator};
@@ -36,4 +49,6 @@ pub use engine::{Engine, EngineInfo, EngineRegistry};
 pub use error::{ApiError, ApiResult};
 pub use graphql::{create_schema, ApiSchema};
 pub use job::JobStore;
-pub use models::{Job, JobStatus, JobStatusResponse};
+pub use models::{Job, JobPriority, JobStatus, JobStatusResponse};
+pub use queue::{JobQueue, QueueConfig, QueuedJob};
+pub use router::build_router;
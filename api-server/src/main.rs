@@ -18,31 +18,14 @@
 //! - `API_PORT`：API 伺服器埠號（預設 7890）
 //! - `BACKEND_URL`：ConvertX-CN 後端 URL（預設 http://localhost:3000）
 
-// 允許未使用的代碼，因為這些是公共 API 的一部分
-#![allow(dead_code)]
-
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
-use axum::{
-    extract::State,
-    routing::{get, post},
-    Router,
-};
 use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
+use std::time::Duration;
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 
-mod auth;
-mod config;
-mod engine;
-mod error;
-mod graphql;
-mod handlers;
-mod job;
-mod models;
-
-use auth::AppState;
-use config::AppConfig;
+use convertx_api::{
+    build_router, catalog, graphql, handlers, janitor, recovery, sync, AppConfig, AppState,
+};
 
 #[tokio::main]
 async fn main() {
@@ -72,8 +55,57 @@ async fn main() {
     let schema = graphql::create_schema(state.clone());
     let state = state.with_graphql_schema(schema);
 
+    // 復原重啟前未完成的任務
+    recovery::recover_jobs(&state).await;
+
+    // 啟動任務佇列
+    let runner_state = state.clone();
+    state.job_queue.start(move |job_id, cancel| {
+        handlers::run_queued_job(runner_state.clone(), job_id, cancel)
+    });
+    info!(
+        "⚙️ Job queue started ({} workers)",
+        state.config.max_workers
+    );
+
+    // 啟動過期任務清理
+    let policy = state.config.retention_policy();
+    if janitor::start(state.clone(), policy).is_some() {
+        info!(
+            "🧹 Retention sweeper started (completed {}h, failed {}h, every {}s)",
+            state.config.completed_job_ttl_hours,
+            state.config.failed_job_ttl_hours,
+            state.config.cleanup_interval_secs
+        );
+    }
+
+    // 監看引擎設定檔
+    if let Some(path) = state.config.engine_config_path.clone() {
+        catalog::start_reloader(
+            state.engine_registry.clone(),
+            path.clone().into(),
+            Duration::from_secs(state.config.engine_reload_interval_secs),
+        );
+        info!("👀 Watching engine catalog {} (SIGHUP to reload)", path);
+    }
+
+    // 從後端同步引擎清單
+    let sync_interval = Duration::from_secs(state.config.engine_sync_interval_secs);
+    if sync::start(
+        state.engine_registry.clone(),
+        state.config.backend_url.clone(),
+        sync_interval,
+    )
+    .is_some()
+    {
+        info!(
+            "🔄 Engine sync started (every {}s)",
+            state.config.engine_sync_interval_secs
+        );
+    }
+
     // 建立路由
-    let app = create_router(state);
+    let app = build_router(state);
 
     // 啟動伺服器
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
@@ -81,69 +113,3 @@ async fn main() {
     info!("📊 GraphQL Playground: http://{}/graphql", addr);
     axum::serve(listener, app).await.unwrap();
 }
-
-/// GraphQL 處理器
-async fn graphql_handler(
-    State(state): State<AppState>,
-    req: GraphQLRequest,
-) -> GraphQLResponse {
-    if let Some(schema) = &state.graphql_schema {
-        schema.execute(req.into_inner()).await.into()
-    } else {
-        GraphQLResponse::from(async_graphql::Response::from_errors(vec![
-            async_graphql::ServerError::new("GraphQL not initialized", None)
-        ]))
-    }
-}
-
-/// GraphQL Playground HTML
-async fn graphql_playground() -> axum::response::Html<&'static str> {
-    axum::response::Html(r#"
-<!DOCTYPE html>
-<html>
-<head>
-    <title>ConvertX API - GraphQL Playground</title>
-    <link rel="stylesheet" href="https://unpkg.com/graphiql@3.7.2/graphiql.min.css" />
-    <style>
-        body { margin: 0; height: 100vh; }
-        #graphiql { height: 100%; }
-    </style>
-</head>
-<body>
-    <div id="graphiql"></div>
-    <script src="https://unpkg.com/react@18.3.1/umd/react.production.min.js" crossorigin></script>
-    <script src="https://unpkg.com/react-dom@18.3.1/umd/react-dom.production.min.js" crossorigin></script>
-    <script src="https://unpkg.com/graphiql@3.7.2/graphiql.min.js" crossorigin></script>
-    <script>
-        const root = ReactDOM.createRoot(document.getElementById('graphiql'));
-        const fetcher = GraphiQL.createFetcher({ url: '/graphql' });
-        root.render(React.createElement(GraphiQL, { fetcher }));
-    </script>
-</body>
-</html>
-"#)
-}
-
-/// 建立 API 路由
-fn create_router(state: AppState) -> Router {
-    // CORS 設定
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    Router::new()
-        // 健康檢查（無需認證）
-        .route("/api/health", get(handlers::health_check))
-        .route("/health", get(handlers::health_check))
-        // GraphQL（無需認證，Token 在請求中傳遞）
-        .route("/graphql", get(graphql_playground).post(graphql_handler))
-        // API v1 路由（需要認證）
-        .route("/api/v1/engines", get(handlers::list_engines))
-        .route("/api/v1/engines/{engine_id}", get(handlers::get_engine))
-        .route("/api/v1/convert", post(handlers::create_conversion))
-        .route("/api/v1/jobs/{job_id}", get(handlers::get_job_status))
-        .route("/api/v1/jobs/{job_id}/download", get(handlers::download_job_result))
-        .layer(cors)
-        .with_state(state)
-}
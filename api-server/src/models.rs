@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::planner::{ConversionPlan, ConversionStep};
+
 /// API 回應包裝
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T: Serialize> {
@@ -52,6 +54,14 @@ pub struct EngineDetailResponse {
     pub engine: super::engine::EngineInfo,
 }
 
+/// 引擎轉換對照表回應
+#[derive(Debug, Serialize)]
+pub struct EngineConversionsResponse {
+    pub engine_id: String,
+    /// 輸入格式 → 可轉換的輸出格式
+    pub conversions: std::collections::BTreeMap<String, Vec<String>>,
+}
+
 /// 轉換請求參數
 #[derive(Debug, Deserialize)]
 pub struct ConvertParams {
@@ -62,16 +72,75 @@ pub struct ConvertParams {
     /// 額外參數
     #[serde(default)]
     pub options: Option<serde_json::Value>,
+    /// 優先權（可選，預設 normal；high 需要 `priority:high` 權限）
+    #[serde(default)]
+    pub priority: Option<JobPriority>,
+}
+
+/// 轉換計畫預覽請求
+#[derive(Debug, Deserialize)]
+pub struct ConversionPlanRequest {
+    /// 輸入格式
+    pub input_format: String,
+    /// 目標格式
+    pub output_format: String,
+    /// 引擎 ID（可選，自動選擇）
+    pub engine_id: Option<String>,
+    /// 檔案大小（bytes，可選）
+    pub file_size: Option<u64>,
+    /// 額外參數
+    #[serde(default)]
+    pub options: Option<serde_json::Value>,
+}
+
+/// 轉換計畫預覽回應
+#[derive(Debug, Serialize)]
+pub struct ConversionPlanResponse {
+    pub input_format: String,
+    pub output_format: String,
+    /// 可一步完成此轉換的引擎，依選擇順序排列
+    pub engine_candidates: Vec<String>,
+    /// 依序執行的步驟
+    pub steps: Vec<ConversionStep>,
 }
 
 /// 轉換任務回應
 #[derive(Debug, Serialize)]
 pub struct ConvertResponse {
     pub job_id: String,
+    /// 選用的引擎
+    pub engine_id: String,
+    /// 支援此轉換的引擎，依選擇順序排列
+    pub engine_candidates: Vec<String>,
+    /// 建立任務時的警告
+    pub warnings: Vec<String>,
     pub status: String,
     pub message: String,
 }
 
+/// 建立可續傳上傳的請求
+#[derive(Debug, Deserialize)]
+pub struct CreateUploadRequest {
+    /// 原始檔名（決定輸入格式）
+    pub filename: String,
+    /// 檔案大小（bytes）
+    pub size: u64,
+}
+
+/// 刪除上傳工作階段回應
+#[derive(Debug, Serialize)]
+pub struct DeleteUploadResponse {
+    pub upload_id: String,
+    pub message: String,
+}
+
+/// 刪除任務回應
+#[derive(Debug, Serialize)]
+pub struct DeleteJobResponse {
+    pub job_id: String,
+    pub message: String,
+}
+
 /// 任務狀態
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -84,6 +153,8 @@ pub enum JobStatus {
     Completed,
     /// 失敗
     Failed,
+    /// 已取消
+    Cancelled,
 }
 
 impl std::fmt::Display for JobStatus {
@@ -93,6 +164,70 @@ impl std::fmt::Display for JobStatus {
             JobStatus::Processing => write!(f, "processing"),
             JobStatus::Completed => write!(f, "completed"),
             JobStatus::Failed => write!(f, "failed"),
+            JobStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl JobStatus {
+    /// 是否已結束（完成、失敗或取消）
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(JobStatus::Pending),
+            "processing" => Ok(JobStatus::Processing),
+            "completed" => Ok(JobStatus::Completed),
+            "failed" => Ok(JobStatus::Failed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            _ => Err(format!("Unknown job status: {}", s)),
+        }
+    }
+}
+
+/// 任務優先權
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum JobPriority {
+    /// 低（批次任務）
+    Low,
+    /// 一般
+    #[default]
+    Normal,
+    /// 高（互動式轉換）
+    High,
+}
+
+impl std::fmt::Display for JobPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobPriority::Low => write!(f, "low"),
+            JobPriority::Normal => write!(f, "normal"),
+            JobPriority::High => write!(f, "high"),
+        }
+    }
+}
+
+impl std::str::FromStr for JobPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(JobPriority::Low),
+            "normal" => Ok(JobPriority::Normal),
+            "high" => Ok(JobPriority::High),
+            _ => Err(format!("Unknown job priority: {}", s)),
         }
     }
 }
@@ -124,8 +259,20 @@ pub struct Job {
     pub created_at: i64,
     /// 更新時間
     pub updated_at: i64,
-    /// 完成時間
+    /// 結束時間（完成、失敗或取消）
     pub completed_at: Option<i64>,
+    /// 轉換參數
+    pub options: Option<serde_json::Value>,
+    /// 優先權
+    pub priority: JobPriority,
+    /// 支援此轉換的引擎，依選擇順序排列
+    pub engine_candidates: Vec<String>,
+    /// 多步驟轉換的步驟；為空時由 `engine_id` 一步完成
+    pub steps: Vec<ConversionStep>,
+    /// 建立任務時的警告（例如副檔名與檔案內容不符）
+    pub warnings: Vec<String>,
+    /// 輸出檔案的 SHA-256（hex），用於下載的 ETag
+    pub output_checksum: Option<String>,
 }
 
 impl Job {
@@ -152,6 +299,72 @@ impl Job {
             created_at: now,
             updated_at: now,
             completed_at: None,
+            options: None,
+            priority: JobPriority::Normal,
+            engine_candidates: Vec::new(),
+            steps: Vec::new(),
+            warnings: Vec::new(),
+            output_checksum: None,
+        }
+    }
+
+    /// 設定轉換參數
+    pub fn with_options(mut self, options: Option<serde_json::Value>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// 設定優先權
+    pub fn with_priority(mut self, priority: JobPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 設定候選引擎
+    pub fn with_engine_candidates(mut self, engine_candidates: Vec<String>) -> Self {
+        self.engine_candidates = engine_candidates;
+        self
+    }
+
+    /// 設定多步驟轉換的步驟
+    pub fn with_steps(mut self, steps: Vec<ConversionStep>) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// 設定警告
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// 上傳檔案儲存的檔名
+    ///
+    /// 只取原始檔名的最後一段，不含任何路徑；
+    /// 副檔名與輸入格式不同（缺少或與內容不符）時改用輸入格式作為副檔名，讓後端依正確格式處理。
+    pub fn upload_filename(&self) -> String {
+        let name = std::path::Path::new(&self.original_filename)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("upload");
+        let path = std::path::Path::new(name);
+        let matches = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case(&self.input_format));
+        if matches {
+            return name.to_string();
+        }
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+        format!("{}.{}", stem, self.input_format)
+    }
+
+    /// 實際執行的轉換步驟
+    pub fn conversion_steps(&self) -> Vec<ConversionStep> {
+        if self.steps.is_empty() {
+            ConversionPlan::single(&self.engine_id, &self.input_format, &self.output_format).steps
+        } else {
+            self.steps.clone()
         }
     }
 }
@@ -166,11 +379,20 @@ pub struct JobStatusResponse {
     pub input_format: String,
     pub output_format: String,
     pub engine_id: String,
+    /// 支援此轉換的引擎，依選擇順序排列
+    pub engine_candidates: Vec<String>,
+    /// 轉換步驟
+    pub steps: Vec<ConversionStep>,
+    /// 建立任務時的警告
+    pub warnings: Vec<String>,
+    pub priority: JobPriority,
     pub error_message: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
     pub completed_at: Option<i64>,
     pub download_ready: bool,
+    /// 在佇列中的位置（從 1 開始，未在等待時為 null）
+    pub queue_position: Option<usize>,
 }
 
 impl From<&Job> for JobStatusResponse {
@@ -183,19 +405,88 @@ impl From<&Job> for JobStatusResponse {
             input_format: job.input_format.clone(),
             output_format: job.output_format.clone(),
             engine_id: job.engine_id.clone(),
+            engine_candidates: job.engine_candidates.clone(),
+            steps: job.conversion_steps(),
+            warnings: job.warnings.clone(),
+            priority: job.priority,
             error_message: job.error_message.clone(),
             created_at: job.created_at,
             updated_at: job.updated_at,
             completed_at: job.completed_at,
             download_ready: job.status == JobStatus::Completed && job.output_file.is_some(),
+            queue_position: None,
         }
     }
 }
 
+/// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// 由舊到新
+    Asc,
+    /// 由新到舊
+    #[default]
+    Desc,
+}
+
+/// 任務列表查詢參數
+#[derive(Debug, Default, Deserialize)]
+pub struct JobListQuery {
+    /// 任務狀態
+    pub status: Option<JobStatus>,
+    /// 引擎 ID
+    pub engine_id: Option<String>,
+    /// 輸出格式
+    pub output_format: Option<String>,
+    /// 建立時間下限（Unix 秒，含）
+    pub created_after: Option<i64>,
+    /// 建立時間上限（Unix 秒，不含）
+    pub created_before: Option<i64>,
+    /// 依建立時間排序（預設由新到舊）
+    #[serde(default)]
+    pub order: SortOrder,
+    /// 上一頁回傳的 `next_cursor`
+    pub cursor: Option<String>,
+    /// 每頁筆數（預設 20，最多 100）
+    pub limit: Option<usize>,
+}
+
+/// 下載格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadFormat {
+    /// 直接下載轉換結果
+    #[default]
+    Raw,
+    /// 包裝成 ZIP
+    Zip,
+}
+
+/// 下載查詢參數
+#[derive(Debug, Default, Deserialize)]
+pub struct DownloadQuery {
+    /// 下載格式（預設 `raw`）
+    #[serde(default)]
+    pub format: DownloadFormat,
+}
+
+/// 任務列表回應
+#[derive(Debug, Serialize)]
+pub struct JobListResponse {
+    pub jobs: Vec<JobStatusResponse>,
+    /// 符合篩選條件的任務總數
+    pub total: usize,
+    /// 下一頁游標，沒有下一頁時為 null
+    pub next_cursor: Option<String>,
+}
+
 /// 健康檢查回應
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
     pub backend_status: String,
+    /// 啟動以來過期清理的累計
+    pub retention: crate::janitor::RetentionTotals,
 }
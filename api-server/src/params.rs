@@ -0,0 +1,186 @@
+//! 引擎參數驗證模組
+//!
+//! 依引擎的 `params_schema` 驗證轉換選項，支援 JSON Schema 的常用子集：
+//! `type`、`enum`、`required`、`properties`、`additionalProperties`（布林值）、
+//! `items`、`minimum` / `maximum`、`minLength` / `maxLength`。
+//! 其他關鍵字會被忽略。
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// 單一參數驗證錯誤
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamViolation {
+    /// 出錯的欄位路徑（例如 `options.target_lang`）
+    pub path: String,
+    /// 錯誤說明
+    pub message: String,
+}
+
+/// 依 schema 驗證轉換選項，回傳所有不符合的項目
+///
+/// 未提供選項時視為空物件，讓 `required` 檢查生效。
+pub fn validate_options(schema: &Value, options: Option<&Value>) -> Vec<ParamViolation> {
+    let empty = Value::Object(Default::default());
+    let mut violations = Vec::new();
+    validate_value(
+        schema,
+        options.unwrap_or(&empty),
+        "options",
+        &mut violations,
+    );
+    violations
+}
+
+/// 只保留 schema `properties` 宣告過的選項
+///
+/// 多步驟轉換時每個步驟只收到自己引擎宣告的選項；沒有可傳的選項時回傳 None。
+pub fn declared_options(schema: Option<&Value>, options: Option<&Value>) -> Option<Value> {
+    let properties = schema?.get("properties")?.as_object()?;
+    let declared: serde_json::Map<String, Value> = options?
+        .as_object()?
+        .iter()
+        .filter(|(key, _)| properties.contains_key(*key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    (!declared.is_empty()).then_some(Value::Object(declared))
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str, violations: &mut Vec<ParamViolation>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let mut violation = |message: String| {
+        violations.push(ParamViolation {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(name) => type_matches(name, value),
+            Value::Array(names) => names
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|name| type_matches(name, value)),
+            _ => true,
+        };
+        if !matches {
+            violation(format!(
+                "expected {}, got {}",
+                describe_type(expected),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let choices: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            violation(format!("must be one of: {}", choices.join(", ")));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                violation(format!("must be >= {}", minimum));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                violation(format!("must be <= {}", maximum));
+            }
+        }
+    }
+
+    if let Some(text) = value.as_str() {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                violation(format!("must be at least {} characters", min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                violation(format!("must be at most {} characters", max));
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    violations.push(ParamViolation {
+                        path: format!("{}.{}", path, key),
+                        message: "is required".to_string(),
+                    });
+                }
+            }
+        }
+
+        let additional_allowed = schema
+            .get("additionalProperties")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        for (key, item) in object {
+            let item_path = format!("{}.{}", path, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(item_schema) => validate_value(item_schema, item, &item_path, violations),
+                None if !additional_allowed => violations.push(ParamViolation {
+                    path: item_path,
+                    message: "is not a recognized option".to_string(),
+                }),
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            validate_value(items, item, &format!("{}[{}]", path, index), violations);
+        }
+    }
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        Value::String(name) => name.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
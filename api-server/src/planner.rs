@@ -0,0 +1,156 @@
+//! 轉換規劃模組
+//!
+//! 沒有單一引擎支援的轉換，改以多個引擎依序轉換完成（例如 heic → png → pdf）。
+//! 以格式為節點、引擎的轉換組合為邊，以廣度優先搜尋找出步驟最少的路徑：
+//! - 同一層中依優先順序（相同時依 engine_id）展開，結果固定
+//! - 需要額外參數的引擎不參與串接
+//! - 中間檔案大小無法預知，每個步驟的引擎都必須接受原始檔案大小
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::engine::Engine;
+
+/// 串接轉換的最多步驟數
+pub const MAX_PLAN_STEPS: usize = 3;
+
+/// 單一轉換步驟
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversionStep {
+    /// 執行此步驟的引擎
+    pub engine_id: String,
+    /// 輸入格式
+    pub input_format: String,
+    /// 輸出格式
+    pub output_format: String,
+}
+
+/// 轉換計畫
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConversionPlan {
+    /// 依序執行的步驟
+    pub steps: Vec<ConversionStep>,
+}
+
+impl ConversionPlan {
+    /// 由單一引擎完成的計畫
+    pub fn single(engine_id: &str, input: &str, output: &str) -> Self {
+        Self {
+            steps: vec![ConversionStep {
+                engine_id: engine_id.to_string(),
+                input_format: input.to_string(),
+                output_format: output.to_string(),
+            }],
+        }
+    }
+
+    /// 第一個步驟的引擎
+    pub fn first_engine_id(&self) -> &str {
+        &self.steps[0].engine_id
+    }
+
+    /// 是否需要多個步驟
+    pub fn is_multi_step(&self) -> bool {
+        self.steps.len() > 1
+    }
+}
+
+/// 以多個引擎串接完成轉換，找不到 `MAX_PLAN_STEPS` 步內的路徑時回傳 None
+pub fn plan_chain(
+    engines: &[&Engine],
+    input: &str,
+    output: &str,
+    file_size: u64,
+) -> Option<ConversionPlan> {
+    let input = input.to_lowercase();
+    let output = output.to_lowercase();
+    if input == output {
+        return None;
+    }
+
+    let edges = conversion_edges(engines, file_size);
+    let mut reached: HashMap<String, ConversionStep> = HashMap::new();
+    let mut frontier = vec![input.clone()];
+
+    for _ in 0..MAX_PLAN_STEPS {
+        let mut next = Vec::new();
+        for format in &frontier {
+            for (engine, target) in edges.get(format).into_iter().flatten() {
+                if *target == input || reached.contains_key(target) {
+                    continue;
+                }
+                reached.insert(
+                    target.clone(),
+                    ConversionStep {
+                        engine_id: engine.engine_id.clone(),
+                        input_format: format.clone(),
+                        output_format: target.clone(),
+                    },
+                );
+                if *target == output {
+                    return Some(trace_back(&reached, &input, &output));
+                }
+                next.push(target.clone());
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    None
+}
+
+/// 各輸入格式可走的邊（引擎、輸出格式），依優先順序排列
+fn conversion_edges<'a>(
+    engines: &[&'a Engine],
+    file_size: u64,
+) -> HashMap<String, Vec<(&'a Engine, String)>> {
+    let mut edges: HashMap<String, Vec<(&'a Engine, String)>> = HashMap::new();
+
+    for engine in engines {
+        if !engine.is_usable() || engine.requires_params || !engine.accepts_file_size(file_size) {
+            continue;
+        }
+        for (input, outputs) in engine.conversion_matrix() {
+            let input = input.to_lowercase();
+            let mut seen = HashSet::new();
+            for output in outputs {
+                let output = output.to_lowercase();
+                if output != input && seen.insert(output.clone()) {
+                    edges
+                        .entry(input.clone())
+                        .or_default()
+                        .push((*engine, output));
+                }
+            }
+        }
+    }
+
+    for (input, list) in edges.iter_mut() {
+        list.sort_by(|(a, a_output), (b, b_output)| {
+            b.priority_for(input, b_output)
+                .cmp(&a.priority_for(input, a_output))
+                .then_with(|| a.engine_id.cmp(&b.engine_id))
+                .then_with(|| a_output.cmp(b_output))
+        });
+    }
+    edges
+}
+
+/// 從輸出格式回溯到輸入格式，組成步驟清單
+fn trace_back(
+    reached: &HashMap<String, ConversionStep>,
+    input: &str,
+    output: &str,
+) -> ConversionPlan {
+    let mut steps = Vec::new();
+    let mut format = output.to_string();
+    while format != input {
+        let step = reached[&format].clone();
+        format = step.input_format.clone();
+        steps.push(step);
+    }
+    steps.reverse();
+    ConversionPlan { steps }
+}
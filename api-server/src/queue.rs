@@ -0,0 +1,346 @@
+//! 任務佇列模組
+//!
+//! 取代每個上傳直接 `tokio::spawn` 的作法：
+//! - 全域工作者數量上限
+//! - 各引擎獨立的並行上限（FFmpeg、MinerU 等重量級引擎）；多步驟轉換執行期間佔用每個步驟引擎的名額
+//! - 依任務優先權派送，同一優先權內使用者之間輪流派送（round-robin）
+//! - 每位使用者進行中任務數上限
+//! - 可查詢任務在佇列中的位置
+//! - 取消等待中的任務，或透過 `CancellationToken` 中止執行中的任務
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::ApiError;
+use crate::models::{Job, JobPriority};
+
+/// 派送時依序檢查的優先權
+const PRIORITY_ORDER: [JobPriority; 3] = [JobPriority::High, JobPriority::Normal, JobPriority::Low];
+
+/// 佇列設定
+#[derive(Debug, Clone)]
+pub struct QueueConfig {
+    /// 全域同時執行的任務上限
+    pub max_workers: usize,
+    /// 各引擎同時執行的任務上限（未列出的引擎僅受全域上限限制）
+    pub engine_limits: HashMap<String, usize>,
+    /// 每位使用者進行中（等待 + 執行）的任務上限，0 表示不限制
+    pub max_jobs_per_user: usize,
+}
+
+impl QueueConfig {
+    /// 取得引擎的並行上限
+    pub fn engine_limit(&self, engine_id: &str) -> usize {
+        self.engine_limits
+            .get(engine_id)
+            .copied()
+            .unwrap_or(self.max_workers)
+    }
+}
+
+/// 佇列中的任務
+#[derive(Debug, Clone)]
+pub struct QueuedJob {
+    /// 任務 ID
+    pub job_id: String,
+    /// 執行期間佔用名額的引擎（多步驟轉換的每個步驟引擎，不重複）
+    pub engine_ids: Vec<String>,
+    /// 任務擁有者
+    pub user_id: String,
+    /// 優先權
+    pub priority: JobPriority,
+}
+
+impl QueuedJob {
+    /// 由已儲存的任務建立
+    pub fn for_job(job: &Job) -> Self {
+        let mut engine_ids: Vec<String> = Vec::new();
+        for step in job.conversion_steps() {
+            if !engine_ids.contains(&step.engine_id) {
+                engine_ids.push(step.engine_id);
+            }
+        }
+        Self {
+            job_id: job.job_id.clone(),
+            engine_ids,
+            user_id: job.user_id.clone(),
+            priority: job.priority,
+        }
+    }
+}
+
+/// 佇列內部狀態
+#[derive(Default)]
+struct QueueState {
+    /// 等待中的任務（依到達順序）
+    pending: Vec<QueuedJob>,
+    /// 使用者輪替順序，最近被服務的使用者移到尾端
+    rotation: VecDeque<String>,
+    /// 執行中的任務（job_id → 任務與取消權杖）
+    running: HashMap<String, (QueuedJob, CancellationToken)>,
+    /// 各引擎執行中的任務數
+    running_per_engine: HashMap<String, usize>,
+}
+
+impl QueueState {
+    /// 使用者進行中（等待 + 執行）的任務數
+    fn in_flight(&self, user_id: &str) -> usize {
+        self.pending.iter().filter(|j| j.user_id == user_id).count()
+            + self
+                .running
+                .values()
+                .filter(|(j, _)| j.user_id == user_id)
+                .count()
+    }
+
+    /// 加入等待中的任務
+    fn push(&mut self, job: QueuedJob) {
+        if !self.rotation.contains(&job.user_id) {
+            self.rotation.push_back(job.user_id.clone());
+        }
+        self.pending.push(job);
+    }
+
+    /// 依優先權與輪替規則排列的等待任務（不考慮引擎名額）
+    fn dispatch_order(&self) -> Vec<&QueuedJob> {
+        let mut order = Vec::with_capacity(self.pending.len());
+
+        for priority in PRIORITY_ORDER {
+            let mut per_user: Vec<VecDeque<&QueuedJob>> = self
+                .rotation
+                .iter()
+                .map(|user| {
+                    self.pending
+                        .iter()
+                        .filter(|j| &j.user_id == user && j.priority == priority)
+                        .collect()
+                })
+                .collect();
+
+            loop {
+                let before = order.len();
+                for jobs in per_user.iter_mut() {
+                    if let Some(job) = jobs.pop_front() {
+                        order.push(job);
+                    }
+                }
+                if order.len() == before {
+                    break;
+                }
+            }
+        }
+        order
+    }
+}
+
+/// 任務佇列
+#[derive(Clone)]
+pub struct JobQueue {
+    config: Arc<QueueConfig>,
+    state: Arc<Mutex<QueueState>>,
+    notify: Arc<Notify>,
+}
+
+impl JobQueue {
+    /// 建立新的任務佇列
+    pub fn new(config: QueueConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            state: Arc::new(Mutex::new(QueueState::default())),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 佇列設定
+    pub fn config(&self) -> &QueueConfig {
+        &self.config
+    }
+
+    /// 檢查使用者是否還能提交新任務
+    ///
+    /// 僅供提早拒絕（例如讀取上傳內容前）；實際的上限由 `enqueue` 保證。
+    pub async fn check_user_quota(&self, user_id: &str) -> Result<(), ApiError> {
+        self.check_quota(&*self.state.lock().await, user_id)
+    }
+
+    /// 將任務排入佇列
+    ///
+    /// 檢查每人上限與排入在同一個鎖內完成，同時提交的任務不會超過上限。
+    pub async fn enqueue(&self, job: QueuedJob) -> Result<(), ApiError> {
+        let mut state = self.state.lock().await;
+        self.check_quota(&state, &job.user_id)?;
+        state.push(job);
+        drop(state);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// 將重啟前的任務重新排入佇列（不受每人上限限制）
+    pub async fn restore(&self, job: QueuedJob) {
+        self.state.lock().await.push(job);
+        self.notify.notify_one();
+    }
+
+    fn check_quota(&self, state: &QueueState, user_id: &str) -> Result<(), ApiError> {
+        let limit = self.config.max_jobs_per_user;
+        if limit > 0 && state.in_flight(user_id) >= limit {
+            return Err(ApiError::TooManyJobs(limit));
+        }
+        Ok(())
+    }
+
+    /// 取得任務在佇列中的位置（從 1 開始）；不在等待中時回傳 None
+    pub async fn position(&self, job_id: &str) -> Option<usize> {
+        let state = self.state.lock().await;
+        state
+            .dispatch_order()
+            .iter()
+            .position(|j| j.job_id == job_id)
+            .map(|i| i + 1)
+    }
+
+    /// 取消任務
+    ///
+    /// 等待中的任務直接移出佇列；執行中的任務觸發其取消權杖。
+    /// 任務不在佇列中時回傳 false。
+    pub async fn cancel(&self, job_id: &str) -> bool {
+        let mut state = self.state.lock().await;
+
+        if let Some(index) = state.pending.iter().position(|j| j.job_id == job_id) {
+            let job = state.pending.remove(index);
+            if !state.pending.iter().any(|j| j.user_id == job.user_id) {
+                state.rotation.retain(|user| user != &job.user_id);
+            }
+            return true;
+        }
+
+        if let Some((_, token)) = state.running.get(job_id) {
+            token.cancel();
+            return true;
+        }
+
+        false
+    }
+
+    /// 使用者進行中（等待 + 執行）的任務數
+    pub async fn user_in_flight(&self, user_id: &str) -> usize {
+        self.state.lock().await.in_flight(user_id)
+    }
+
+    /// 等待中的任務數
+    pub async fn pending_count(&self) -> usize {
+        self.state.lock().await.pending.len()
+    }
+
+    /// 執行中的任務數
+    pub async fn running_count(&self) -> usize {
+        self.state.lock().await.running.len()
+    }
+
+    /// 啟動派送迴圈
+    ///
+    /// `runner` 負責實際執行任務，並應在取消權杖觸發時儘快結束；
+    /// 結束後釋放工作者與引擎名額。
+    pub fn start<F, Fut>(&self, runner: F) -> JoinHandle<()>
+    where
+        F: Fn(String, CancellationToken) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let queue = self.clone();
+        let runner = Arc::new(runner);
+
+        tokio::spawn(async move {
+            loop {
+                while let Some((job, token)) = queue.next_job().await {
+                    let queue = queue.clone();
+                    let runner = runner.clone();
+                    tokio::spawn(async move {
+                        // 以獨立任務執行，即使 runner panic 也能釋放名額
+                        if let Err(e) = tokio::spawn(runner(job.job_id.clone(), token)).await {
+                            tracing::error!("Job {} worker crashed: {}", job.job_id, e);
+                        }
+                        queue.finish(&job).await;
+                    });
+                }
+                queue.notify.notified().await;
+            }
+        })
+    }
+
+    /// 取出下一個可執行的任務並標記為執行中
+    ///
+    /// 先挑最高優先權，同一優先權內依使用者輪替順序挑選，
+    /// 每位使用者取其最早且引擎仍有名額的任務。
+    async fn next_job(&self) -> Option<(QueuedJob, CancellationToken)> {
+        let mut state = self.state.lock().await;
+        if state.running.len() >= self.config.max_workers {
+            return None;
+        }
+
+        let has_capacity = |state: &QueueState, job: &QueuedJob| {
+            job.engine_ids.iter().all(|engine_id| {
+                let running = state
+                    .running_per_engine
+                    .get(engine_id)
+                    .copied()
+                    .unwrap_or(0);
+                running < self.config.engine_limit(engine_id)
+            })
+        };
+
+        let (rotation_index, index) = PRIORITY_ORDER.iter().find_map(|priority| {
+            state
+                .rotation
+                .iter()
+                .enumerate()
+                .find_map(|(rotation_index, user)| {
+                    state
+                        .pending
+                        .iter()
+                        .position(|j| {
+                            &j.user_id == user && j.priority == *priority && has_capacity(&state, j)
+                        })
+                        .map(|index| (rotation_index, index))
+                })
+        })?;
+
+        let job = state.pending.remove(index);
+
+        // 服務過的使用者移到輪替尾端；沒有等待任務時移出輪替
+        if let Some(user) = state.rotation.remove(rotation_index) {
+            if state.pending.iter().any(|j| j.user_id == user) {
+                state.rotation.push_back(user);
+            }
+        }
+
+        let token = CancellationToken::new();
+        state
+            .running
+            .insert(job.job_id.clone(), (job.clone(), token.clone()));
+        for engine_id in &job.engine_ids {
+            *state
+                .running_per_engine
+                .entry(engine_id.clone())
+                .or_insert(0) += 1;
+        }
+        Some((job, token))
+    }
+
+    /// 任務結束，釋放名額並喚醒派送迴圈
+    async fn finish(&self, job: &QueuedJob) {
+        let mut state = self.state.lock().await;
+        if state.running.remove(&job.job_id).is_some() {
+            for engine_id in &job.engine_ids {
+                if let Some(count) = state.running_per_engine.get_mut(engine_id) {
+                    *count = count.saturating_sub(1);
+                }
+            }
+        }
+        drop(state);
+        self.notify.notify_one();
+    }
+}
@@ -0,0 +1,103 @@
+//! 任務復原模組
+//!
+//! 伺服器重新啟動時掃描持久化的任務：
+//! - 等待中且上傳檔案仍存在的任務重新排入佇列
+//! - 處理中（已被中斷）或上傳檔案遺失的任務標記為失敗
+//! - 刪除上傳中斷留下的暫存檔
+
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use crate::auth::AppState;
+use crate::models::JobStatus;
+use crate::queue::QueuedJob;
+use crate::upload::STAGING_PREFIX;
+
+/// 中斷任務的錯誤訊息
+pub const INTERRUPTED_MESSAGE: &str = "伺服器重新啟動，任務處理中斷，請重新提交";
+
+/// 上傳檔案遺失的錯誤訊息
+pub const MISSING_UPLOAD_MESSAGE: &str = "伺服器重新啟動後找不到上傳檔案，請重新提交";
+
+/// 復原結果
+#[derive(Debug, Default)]
+pub struct RecoveryReport {
+    /// 重新排入佇列的任務
+    pub resumed: Vec<String>,
+    /// 標記為失敗的任務
+    pub failed: Vec<String>,
+}
+
+/// 復原重啟前未完成的任務
+pub async fn recover_jobs(state: &AppState) -> RecoveryReport {
+    let mut report = RecoveryReport::default();
+    remove_staged_uploads(state).await;
+
+    let jobs = state
+        .job_store
+        .get_jobs_by_status(&[JobStatus::Pending, JobStatus::Processing])
+        .await;
+
+    for job in jobs {
+        if job.status == JobStatus::Processing {
+            warn!("⚠️ Job {} was interrupted while processing", job.job_id);
+            state
+                .job_store
+                .fail_job(&job.job_id, INTERRUPTED_MESSAGE.to_string())
+                .await;
+            report.failed.push(job.job_id);
+            continue;
+        }
+
+        let upload_path = PathBuf::from(&state.config.upload_dir)
+            .join(&job.job_id)
+            .join(job.upload_filename());
+
+        if !tokio::fs::try_exists(&upload_path).await.unwrap_or(false) {
+            warn!("⚠️ Upload for pending job {} is missing", job.job_id);
+            state
+                .job_store
+                .fail_job(&job.job_id, MISSING_UPLOAD_MESSAGE.to_string())
+                .await;
+            report.failed.push(job.job_id);
+            continue;
+        }
+
+        state.job_queue.restore(QueuedJob::for_job(&job)).await;
+        report.resumed.push(job.job_id);
+    }
+
+    if !report.resumed.is_empty() || !report.failed.is_empty() {
+        info!(
+            "♻️ Job recovery: {} resumed, {} marked as failed",
+            report.resumed.len(),
+            report.failed.len()
+        );
+    }
+
+    report
+}
+
+/// 刪除上傳中斷留下的暫存檔
+async fn remove_staged_uploads(state: &AppState) {
+    let Ok(mut entries) = tokio::fs::read_dir(&state.config.upload_dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(STAGING_PREFIX)
+        {
+            continue;
+        }
+        match tokio::fs::remove_file(entry.path()).await {
+            Ok(()) => info!("🧹 Removed stale upload {}", entry.path().display()),
+            Err(e) => warn!(
+                "⚠️ Failed to remove stale upload {}: {}",
+                entry.path().display(),
+                e
+            ),
+        }
+    }
+}
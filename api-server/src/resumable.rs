@@ -0,0 +1,355 @@
+//! 可續傳上傳模組
+//!
+//! 大型檔案可分段上傳，連線中斷後從伺服器記錄的位移繼續：
+//! 1. 建立上傳工作階段，宣告檔名與檔案大小
+//! 2. 依序以 PATCH 送出區塊，每個區塊附上 SHA-256 校驗值
+//! 3. 全部送達後指定轉換參數，建立轉換任務
+//!
+//! 工作階段記錄在 SQLite，已接收的內容寫在 `upload_dir/.sessions/`；
+//! 閒置超過期限的工作階段由定期清理刪除。
+
+use axum::body::Bytes;
+use futures::{Stream, StreamExt};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::db::Database;
+use crate::error::{ApiError, ApiResult};
+
+/// 工作階段檔案所在的目錄（位於 `upload_dir` 下）
+pub const SESSIONS_DIR: &str = ".sessions";
+
+/// 區塊校驗使用的演算法
+pub const CHECKSUM_ALGORITHM: &str = "sha256";
+
+/// 區塊起始位移的標頭
+pub const UPLOAD_OFFSET_HEADER: &str = "upload-offset";
+
+/// 區塊校驗值的標頭
+pub const UPLOAD_CHECKSUM_HEADER: &str = "upload-checksum";
+
+/// 查詢工作階段時使用的欄位（順序需與 `session_from_row` 一致）
+const SESSION_COLUMNS: &str =
+    "upload_id, user_id, filename, size, received, created_at, expires_at";
+
+/// 上傳工作階段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UploadSession {
+    pub upload_id: String,
+    #[serde(skip)]
+    pub user_id: String,
+    /// 原始檔名
+    pub filename: String,
+    /// 宣告的檔案大小（bytes）
+    pub size: u64,
+    /// 已確認接收的 bytes，下一個區塊須從此位移開始
+    pub offset: u64,
+    pub created_at: i64,
+    /// 閒置到此時間（Unix 秒）後過期，每次接收區塊時延長
+    pub expires_at: i64,
+}
+
+impl UploadSession {
+    /// 建立新的工作階段
+    pub fn new(user_id: String, filename: String, size: u64, ttl_secs: u64) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            upload_id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            filename,
+            size,
+            offset: 0,
+            created_at: now,
+            expires_at: now + ttl_secs as i64,
+        }
+    }
+
+    /// 是否已接收完整檔案
+    pub fn is_complete(&self) -> bool {
+        self.offset == self.size
+    }
+}
+
+/// 檔名是否可用於工作階段（不含路徑）
+pub fn is_valid_filename(filename: &str) -> bool {
+    !filename.is_empty()
+        && filename != "."
+        && filename != ".."
+        && !filename.contains(['/', '\\', '\0'])
+}
+
+/// 工作階段檔案路徑
+pub fn session_path(upload_dir: &str, upload_id: &str) -> PathBuf {
+    PathBuf::from(upload_dir).join(SESSIONS_DIR).join(upload_id)
+}
+
+/// 上傳工作階段儲存器
+///
+/// 同一工作階段同時只允許一個請求寫入，以 `lock` 取得。
+#[derive(Clone)]
+pub struct UploadSessionStore {
+    db: Database,
+    active: Arc<Mutex<HashSet<String>>>,
+}
+
+/// 工作階段寫入鎖，drop 時釋放
+pub struct UploadLock {
+    upload_id: String,
+    active: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Drop for UploadLock {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(&self.upload_id);
+        }
+    }
+}
+
+impl UploadSessionStore {
+    /// 使用既有的資料庫連線
+    pub fn with_database(db: Database) -> Self {
+        Self {
+            db,
+            active: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// 建立工作階段
+    pub async fn create(&self, session: UploadSession) -> ApiResult<UploadSession> {
+        let row = session.clone();
+        self.db
+            .call(move |conn| {
+                conn.execute(
+                    &format!(
+                        "INSERT INTO upload_sessions ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        SESSION_COLUMNS
+                    ),
+                    params![
+                        row.upload_id,
+                        row.user_id,
+                        row.filename,
+                        row.size as i64,
+                        row.offset as i64,
+                        row.created_at,
+                        row.expires_at,
+                    ],
+                )
+            })
+            .await?;
+        Ok(session)
+    }
+
+    /// 取得工作階段
+    pub async fn get(&self, upload_id: &str) -> Option<UploadSession> {
+        let upload_id = upload_id.to_string();
+        self.query(move |conn| select_session(conn, &upload_id))
+            .await
+            .flatten()
+    }
+
+    /// 記錄已確認接收的位移並延長期限
+    pub async fn advance(
+        &self,
+        upload_id: &str,
+        offset: u64,
+        expires_at: i64,
+    ) -> Option<UploadSession> {
+        let upload_id = upload_id.to_string();
+        self.query(move |conn| {
+            conn.execute(
+                "UPDATE upload_sessions SET received = ?2, expires_at = ?3 WHERE upload_id = ?1",
+                params![upload_id, offset as i64, expires_at],
+            )?;
+            select_session(conn, &upload_id)
+        })
+        .await
+        .flatten()
+    }
+
+    /// 刪除工作階段
+    pub async fn delete(&self, upload_id: &str) -> Option<UploadSession> {
+        let upload_id = upload_id.to_string();
+        self.query(move |conn| {
+            let tx = conn.transaction()?;
+            let session = select_session(&tx, &upload_id)?;
+            if session.is_some() {
+                tx.execute(
+                    "DELETE FROM upload_sessions WHERE upload_id = ?1",
+                    params![upload_id],
+                )?;
+            }
+            tx.commit()?;
+            Ok(session)
+        })
+        .await
+        .flatten()
+    }
+
+    /// 刪除並回傳在 `now` 之前過期的工作階段（寫入中的略過）
+    pub async fn take_expired(&self, now: i64) -> Vec<UploadSession> {
+        let expired = self
+            .query(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM upload_sessions WHERE expires_at <= ?1 ORDER BY expires_at ASC",
+                    SESSION_COLUMNS
+                ))?;
+                let sessions = stmt
+                    .query_map(params![now], session_from_row)?
+                    .collect::<rusqlite::Result<Vec<UploadSession>>>()?;
+                Ok(sessions)
+            })
+            .await
+            .unwrap_or_default();
+
+        let mut taken = Vec::new();
+        for session in expired {
+            let Some(_lock) = self.lock(&session.upload_id) else {
+                continue;
+            };
+            if let Some(session) = self.delete(&session.upload_id).await {
+                taken.push(session);
+            }
+        }
+        taken
+    }
+
+    /// 取得工作階段的寫入鎖，已有其他請求寫入時回傳 None
+    pub fn lock(&self, upload_id: &str) -> Option<UploadLock> {
+        let mut active = self.active.lock().ok()?;
+        if !active.insert(upload_id.to_string()) {
+            return None;
+        }
+        Some(UploadLock {
+            upload_id: upload_id.to_string(),
+            active: self.active.clone(),
+        })
+    }
+
+    /// 執行查詢，失敗時記錄錯誤並回傳 None
+    async fn query<F, T>(&self, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        match self.db.call(f).await {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::error!("Upload session query failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// 依 ID 查詢單一工作階段
+fn select_session(conn: &Connection, upload_id: &str) -> rusqlite::Result<Option<UploadSession>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM upload_sessions WHERE upload_id = ?1",
+            SESSION_COLUMNS
+        ),
+        params![upload_id],
+        session_from_row,
+    )
+    .optional()
+}
+
+fn session_from_row(row: &Row<'_>) -> rusqlite::Result<UploadSession> {
+    Ok(UploadSession {
+        upload_id: row.get(0)?,
+        user_id: row.get(1)?,
+        filename: row.get(2)?,
+        size: row.get::<_, i64>(3)? as u64,
+        offset: row.get::<_, i64>(4)? as u64,
+        created_at: row.get(5)?,
+        expires_at: row.get(6)?,
+    })
+}
+
+/// 解析區塊校驗值（`sha256 <hex>`），回傳小寫的 hex 字串
+pub fn parse_checksum(value: &str) -> Result<String, ApiError> {
+    let invalid = || {
+        ApiError::InvalidInput(format!(
+            "Invalid Upload-Checksum: expected '{} <hex digest>'",
+            CHECKSUM_ALGORITHM
+        ))
+    };
+    let (algorithm, digest) = value.trim().split_once(' ').ok_or_else(invalid)?;
+    let digest = digest.trim();
+    if !algorithm.eq_ignore_ascii_case(CHECKSUM_ALGORITHM)
+        || digest.len() != 64
+        || !digest.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(invalid());
+    }
+    Ok(digest.to_ascii_lowercase())
+}
+
+/// 將區塊寫入工作階段檔案的 `offset` 位置，回傳寫入後的位移
+///
+/// 先捨棄 `offset` 之後未確認的內容（上次中斷時寫入的部分）。
+/// 超過 `size` 或校驗不符時還原到 `offset`，不影響已確認的內容。
+pub async fn write_chunk<S>(
+    path: &Path,
+    offset: u64,
+    size: u64,
+    mut body: S,
+    checksum: &str,
+) -> Result<u64, ApiError>
+where
+    S: Stream<Item = Result<Bytes, axum::Error>> + Unpin,
+{
+    let io_error =
+        |e: std::io::Error| ApiError::InternalError(format!("Failed to write chunk: {}", e));
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .await
+        .map_err(io_error)?;
+    file.set_len(offset).await.map_err(io_error)?;
+    file.seek(SeekFrom::Start(offset)).await.map_err(io_error)?;
+
+    let mut end = offset;
+    let written: Result<(), ApiError> = async {
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk
+                .map_err(|e| ApiError::InvalidInput(format!("Failed to read chunk: {}", e)))?;
+            end += chunk.len() as u64;
+            if end > size {
+                return Err(ApiError::InvalidInput(format!(
+                    "Chunk exceeds declared upload size of {} bytes",
+                    size
+                )));
+            }
+            hasher.update(&chunk);
+            file.write_all(&chunk).await.map_err(io_error)?;
+        }
+        let digest: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        if digest != checksum {
+            return Err(ApiError::ChecksumMismatch);
+        }
+        file.sync_data().await.map_err(io_error)
+    }
+    .await;
+
+    if let Err(e) = written {
+        let _ = file.set_len(offset).await;
+        return Err(e);
+    }
+    Ok(end)
+}
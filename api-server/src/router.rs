@@ -0,0 +1,122 @@
+//! 路由模組
+
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::HeaderMap,
+    routing::{get, patch, post},
+    Router,
+};
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::auth::{AppState, AuthenticatedUser};
+use crate::handlers;
+
+/// GraphQL 處理器
+///
+/// 帶有有效 Bearer Token 時，已認證使用者會放入 GraphQL context。
+async fn graphql_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    req: GraphQLRequest,
+) -> GraphQLResponse {
+    if let Some(schema) = &state.graphql_schema {
+        let mut request = req.into_inner();
+        if let Ok(user) = AuthenticatedUser::from_headers(&headers, &state) {
+            request = request.data(user);
+        }
+        schema.execute(request).await.into()
+    } else {
+        GraphQLResponse::from(async_graphql::Response::from_errors(vec![
+            async_graphql::ServerError::new("GraphQL not initialized", None)
+        ]))
+    }
+}
+
+/// GraphQL Playground HTML
+async fn graphql_playground() -> axum::response::Html<&'static str> {
+    axum::response::Html(r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <title>ConvertX API - GraphQL Playground</title>
+    <link rel="stylesheet" href="https://unpkg.com/graphiql@3.7.2/graphiql.min.css" />
+    <style>
+        body { margin: 0; height: 100vh; }
+        #graphiql { height: 100%; }
+    </style>
+</head>
+<body>
+    <div id="graphiql"></div>
+    <script src="https://unpkg.com/react@18.3.1/umd/react.production.min.js" crossorigin></script>
+    <script src="https://unpkg.com/react-dom@18.3.1/umd/react-dom.production.min.js" crossorigin></script>
+    <script src="https://unpkg.com/graphiql@3.7.2/graphiql.min.js" crossorigin></script>
+    <script>
+        const root = ReactDOM.createRoot(document.getElementById('graphiql'));
+        const fetcher = GraphiQL.createFetcher({ url: '/graphql' });
+        root.render(React.createElement(GraphiQL, { fetcher }));
+    </script>
+</body>
+</html>
+"#)
+}
+
+/// 上傳請求中檔案以外的 multipart 內容（參數、分隔線）預留的大小
+const MULTIPART_OVERHEAD: u64 = 1024 * 1024;
+
+/// 建立 API 路由
+pub fn build_router(state: AppState) -> Router {
+    // 上傳大小上限（axum 預設只允許 2MB）
+    let upload_limit = DefaultBodyLimit::max(
+        usize::try_from(state.config.max_file_size + MULTIPART_OVERHEAD).unwrap_or(usize::MAX),
+    );
+
+    // CORS 設定
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    Router::new()
+        // 健康檢查（無需認證）
+        .route("/api/health", get(handlers::health_check))
+        .route("/health", get(handlers::health_check))
+        // GraphQL（無需認證，Token 在請求中傳遞）
+        .route("/graphql", get(graphql_playground).post(graphql_handler))
+        // API v1 路由（需要認證）
+        .route("/api/v1/engines", get(handlers::list_engines))
+        .route("/api/v1/engines/{engine_id}", get(handlers::get_engine))
+        .route(
+            "/api/v1/engines/{engine_id}/conversions",
+            get(handlers::get_engine_conversions),
+        )
+        .route(
+            "/api/v1/admin/engines/{engine_id}",
+            patch(handlers::update_engine),
+        )
+        .route(
+            "/api/v1/convert",
+            post(handlers::create_conversion).layer(upload_limit),
+        )
+        .route("/api/v1/convert/plan", post(handlers::plan_conversion))
+        .route("/api/v1/uploads", post(handlers::create_upload))
+        .route(
+            "/api/v1/uploads/{upload_id}",
+            get(handlers::get_upload)
+                .patch(handlers::upload_chunk)
+                .delete(handlers::delete_upload),
+        )
+        .route(
+            "/api/v1/uploads/{upload_id}/complete",
+            post(handlers::complete_upload),
+        )
+        .route("/api/v1/jobs", get(handlers::list_jobs))
+        .route(
+            "/api/v1/jobs/{job_id}",
+            get(handlers::get_job_status).delete(handlers::delete_job),
+        )
+        .route("/api/v1/jobs/{job_id}/cancel", post(handlers::cancel_job))
+        .route("/api/v1/jobs/{job_id}/download", get(handlers::download_job_result))
+        .layer(cors)
+        .with_state(state)
+}
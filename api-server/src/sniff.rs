@@ -0,0 +1,299 @@
+//! 檔案格式偵測模組
+//!
+//! 依檔案開頭的 magic bytes 判斷格式，補足檔名沒有副檔名或副檔名與內容不符的情況。
+//! 只辨識特徵明確的格式；純文字格式（Markdown、CSV 等）與無法區分內容的容器
+//! （一般 ZIP、OLE2）不判斷，仍以副檔名為準。
+
+/// 偵測格式時讀取的檔案開頭長度
+pub const SNIFF_LEN: usize = 8192;
+
+/// 依內容判斷出的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SniffedFormat {
+    /// 判斷出的格式
+    pub format: &'static str,
+    /// 與此內容相符的副檔名
+    pub extensions: &'static [&'static str],
+    /// 僅由兩個 bytes 的短特徵判斷，可能誤判一般檔案；有副檔名時不覆蓋副檔名
+    pub weak: bool,
+}
+
+impl SniffedFormat {
+    /// 副檔名是否與內容相符
+    pub fn matches_extension(&self, extension: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(extension))
+    }
+
+    const fn weak(self) -> Self {
+        Self { weak: true, ..self }
+    }
+}
+
+const fn sniffed(format: &'static str, extensions: &'static [&'static str]) -> SniffedFormat {
+    SniffedFormat {
+        format,
+        extensions,
+        weak: false,
+    }
+}
+
+const PDF: SniffedFormat = sniffed("pdf", &["pdf", "ai"]);
+const PNG: SniffedFormat = sniffed("png", &["png", "apng"]);
+const JPEG: SniffedFormat = sniffed("jpg", &["jpg", "jpeg", "jpe", "jfif"]);
+const GIF: SniffedFormat = sniffed("gif", &["gif"]);
+const WEBP: SniffedFormat = sniffed("webp", &["webp"]);
+const BMP: SniffedFormat = sniffed("bmp", &["bmp", "dib"]);
+const TIFF: SniffedFormat = sniffed(
+    "tiff",
+    &[
+        "tif", "tiff", "dng", "nef", "nrw", "cr2", "arw", "sr2", "srf", "pef", "3fr", "erf", "kdc",
+        "mef", "dcr",
+    ],
+);
+const PSD: SniffedFormat = sniffed("psd", &["psd", "psb"]);
+const JXL: SniffedFormat = sniffed("jxl", &["jxl"]);
+const JP2: SniffedFormat = sniffed("jp2", &["jp2", "j2k", "jpx", "jpf"]);
+const EXR: SniffedFormat = sniffed("exr", &["exr"]);
+const HEIC: SniffedFormat = sniffed(
+    "heic",
+    &["heic", "heif", "heics", "heifs", "hif", "avci", "avcs"],
+);
+const AVIF: SniffedFormat = sniffed("avif", &["avif", "avifs"]);
+const MP4: SniffedFormat = sniffed("mp4", &["mp4", "m4v", "m4a", "m4b", "mov", "3gp", "3g2"]);
+const MOV: SniffedFormat = sniffed("mov", &["mov", "qt", "mp4"]);
+const WEBM: SniffedFormat = sniffed("webm", &["webm", "mkv"]);
+const MKV: SniffedFormat = sniffed("mkv", &["mkv", "mka", "mk3d", "webm"]);
+const AVI: SniffedFormat = sniffed("avi", &["avi"]);
+const WAV: SniffedFormat = sniffed("wav", &["wav", "wave"]);
+const MP3: SniffedFormat = sniffed("mp3", &["mp3"]);
+const FLAC: SniffedFormat = sniffed("flac", &["flac"]);
+const OGG: SniffedFormat = sniffed("ogg", &["ogg", "oga", "ogv", "opus", "spx"]);
+const RTF: SniffedFormat = sniffed("rtf", &["rtf"]);
+const SVG: SniffedFormat = sniffed("svg", &["svg"]);
+const EPUB: SniffedFormat = sniffed("epub", &["epub"]);
+const ODT: SniffedFormat = sniffed("odt", &["odt", "ott"]);
+const ODS: SniffedFormat = sniffed("ods", &["ods", "ots"]);
+const ODP: SniffedFormat = sniffed("odp", &["odp", "otp"]);
+const DOCX: SniffedFormat = sniffed("docx", &["docx", "docm", "dotx", "dotm"]);
+const XLSX: SniffedFormat = sniffed("xlsx", &["xlsx", "xlsm", "xltx", "xltm"]);
+const PPTX: SniffedFormat = sniffed("pptx", &["pptx", "pptm", "ppsx", "potx", "potm"]);
+
+/// 依檔案開頭判斷格式，無法判斷時回傳 None
+pub fn sniff_format(data: &[u8]) -> Option<SniffedFormat> {
+    let data = &data[..data.len().min(SNIFF_LEN)];
+
+    if data.starts_with(b"%PDF-") {
+        return Some(PDF);
+    }
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some(PNG);
+    }
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(JPEG);
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some(GIF);
+    }
+    if is_bmp(data) {
+        return Some(BMP);
+    }
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some(TIFF);
+    }
+    if data.starts_with(b"8BPS") {
+        return Some(PSD);
+    }
+    if data.starts_with(b"\0\0\0\x0cJXL \r\n\x87\n") {
+        return Some(JXL);
+    }
+    if data.starts_with(&[0xFF, 0x0A]) {
+        return Some(JXL.weak());
+    }
+    if data.starts_with(b"\0\0\0\x0cjP  \r\n\x87\n") {
+        return Some(JP2);
+    }
+    if data.starts_with(&[0x76, 0x2F, 0x31, 0x01]) {
+        return Some(EXR);
+    }
+    if data.starts_with(b"{\\rtf") {
+        return Some(RTF);
+    }
+    if data.starts_with(b"fLaC") {
+        return Some(FLAC);
+    }
+    if data.starts_with(b"OggS") {
+        return Some(OGG);
+    }
+    if data.starts_with(b"ID3") {
+        return Some(MP3);
+    }
+    if matches!(data, [0xFF, 0xFB | 0xF3 | 0xF2, ..]) {
+        return Some(MP3.weak());
+    }
+    if data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some(if contains(data, b"webm") { WEBM } else { MKV });
+    }
+    if data.starts_with(b"RIFF") && data.len() >= 12 {
+        return match &data[8..12] {
+            b"WEBP" => Some(WEBP),
+            b"WAVE" => Some(WAV),
+            b"AVI " => Some(AVI),
+            _ => None,
+        };
+    }
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        return sniff_iso_media(data);
+    }
+    if data.starts_with(b"PK\x03\x04") {
+        return sniff_zip(data);
+    }
+    sniff_text(data)
+}
+
+/// BMP 檔頭：`BM`、保留欄位為 0，且 DIB 標頭大小為已知的版本
+fn is_bmp(data: &[u8]) -> bool {
+    if data.len() < 18 || !data.starts_with(b"BM") || data[6..10] != [0, 0, 0, 0] {
+        return false;
+    }
+    let dib_size = u32::from_le_bytes([data[14], data[15], data[16], data[17]]);
+    matches!(dib_size, 12 | 40 | 52 | 56 | 108 | 124)
+}
+
+/// ISO Base Media（MP4 / HEIF 家族）依 `ftyp` 的主要品牌判斷
+fn sniff_iso_media(ftyp: &[u8]) -> Option<SniffedFormat> {
+    match &ftyp[8..12] {
+        b"heic" | b"heix" | b"hevc" | b"hevx" | b"heim" | b"heis" => Some(HEIC),
+        b"avif" | b"avis" => Some(AVIF),
+        // 通用的 HEIF 品牌，AVIF 與 HEIC 都會使用，需再看相容品牌
+        b"mif1" | b"msf1" => sniff_heif_compatible(ftyp),
+        b"qt  " => Some(MOV),
+        b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1" | b"dash"
+        | b"M4V " | b"M4A " | b"mmp4" | b"3gp4" | b"3gp5" | b"3g2a" => Some(MP4),
+        _ => None,
+    }
+}
+
+/// 依 `ftyp` 的相容品牌（次要版本之後，到 box 結尾為止）區分 AVIF 與 HEIC
+fn sniff_heif_compatible(ftyp: &[u8]) -> Option<SniffedFormat> {
+    let size = u32::from_be_bytes([ftyp[0], ftyp[1], ftyp[2], ftyp[3]]) as usize;
+    let brands = ftyp.get(16..size.min(ftyp.len())).unwrap_or_default();
+    let has = |wanted: &[&[u8]]| brands.chunks_exact(4).any(|b| wanted.contains(&b));
+    if has(&[b"avif", b"avis"]) {
+        Some(AVIF)
+    } else if has(&[b"heic", b"heix"]) {
+        Some(HEIC)
+    } else {
+        None
+    }
+}
+
+/// ZIP 容器依內含的檔案判斷（EPUB、OpenDocument、Office Open XML）
+fn sniff_zip(data: &[u8]) -> Option<SniffedFormat> {
+    // EPUB 與 OpenDocument 的第一個項目為未壓縮的 mimetype
+    if data.len() > 30 && data[30..].starts_with(b"mimetype") {
+        let mimetype = &data[38..];
+        if mimetype.starts_with(b"application/epub+zip") {
+            return Some(EPUB);
+        }
+        if mimetype.starts_with(b"application/vnd.oasis.opendocument.text") {
+            return Some(ODT);
+        }
+        if mimetype.starts_with(b"application/vnd.oasis.opendocument.spreadsheet") {
+            return Some(ODS);
+        }
+        if mimetype.starts_with(b"application/vnd.oasis.opendocument.presentation") {
+            return Some(ODP);
+        }
+    }
+    if contains(data, b"[Content_Types].xml") || contains(data, b"_rels/.rels") {
+        if contains(data, b"word/") {
+            return Some(DOCX);
+        }
+        if contains(data, b"xl/") {
+            return Some(XLSX);
+        }
+        if contains(data, b"ppt/") {
+            return Some(PPTX);
+        }
+    }
+    None
+}
+
+/// 有明確開頭標記的文字格式
+fn sniff_text(data: &[u8]) -> Option<SniffedFormat> {
+    let text = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let start = text.iter().position(|b| !b.is_ascii_whitespace())?;
+    let text = &text[start..];
+
+    if text.starts_with(b"<svg") || (text.starts_with(b"<?xml") && contains(text, b"<svg")) {
+        return Some(SVG);
+    }
+    None
+}
+
+/// 讀取檔案開頭（最多 `SNIFF_LEN` bytes）
+pub async fn read_head(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let file = tokio::fs::File::open(path).await?;
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    file.take(SNIFF_LEN as u64).read_to_end(&mut head).await?;
+    Ok(head)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// 上傳檔案的輸入格式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputFormat {
+    /// 使用的格式
+    pub format: String,
+    /// 依內容修正副檔名時的說明
+    pub warning: Option<String>,
+}
+
+/// 決定上傳檔案的輸入格式
+///
+/// 副檔名與內容相符或內容無法判斷時使用副檔名；副檔名缺少或與內容不符時使用內容判斷的格式。
+/// 只由短特徵判斷出的格式不覆蓋副檔名。
+/// 兩者皆無法取得時回傳 None。
+pub fn detect_input_format(filename: &str, data: &[u8]) -> Option<InputFormat> {
+    let extension = std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|e| !e.is_empty())
+        .map(str::to_lowercase);
+    let sniffed = sniff_format(data);
+
+    match (extension, sniffed) {
+        (Some(extension), Some(sniffed))
+            if !sniffed.weak && !sniffed.matches_extension(&extension) =>
+        {
+            Some(InputFormat {
+                format: sniffed.format.to_string(),
+                warning: Some(format!(
+                    "副檔名 .{} 與檔案內容不符，已依內容以 {} 處理",
+                    extension, sniffed.format
+                )),
+            })
+        }
+        (Some(extension), _) => Some(InputFormat {
+            format: extension,
+            warning: None,
+        }),
+        (None, Some(sniffed)) => Some(InputFormat {
+            format: sniffed.format.to_string(),
+            warning: Some(format!(
+                "檔名沒有副檔名，已依檔案內容判斷為 {}",
+                sniffed.format
+            )),
+        }),
+        (None, None) => None,
+    }
+}
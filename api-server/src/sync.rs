@@ -0,0 +1,215 @@
+//! 後端引擎同步模組
+//!
+//! 從 ConvertX 後端的 `GET /api/converters` 取得實際提供的轉換器，與本地引擎清單合併：
+//! - 本地清單提供名稱、描述、檔案大小上限與參數說明等設定
+//! - 輸入 / 輸出格式以後端回報為準
+//! - 後端未回報或標示停用的引擎視為不可用
+//! - 只存在於後端的引擎以預設設定加入
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::catalog::{is_valid_engine_id, log_diff, normalize_format, CatalogDiff};
+use crate::engine::{Engine, EngineRegistry};
+use crate::error::ApiError;
+
+/// 只存在於後端的引擎使用的檔案大小上限（MB）
+const DISCOVERED_MAX_FILE_SIZE_MB: u64 = 500;
+
+/// 後端請求逾時
+const SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 後端回報的轉換器
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BackendConverter {
+    /// 轉換器名稱（忽略大小寫與英數字以外的字元後對應 engine_id，例如 `PDF Packager` → `pdfpackager`）
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// 後端是否可使用（平台不支援時為 false）
+    #[serde(default = "default_available")]
+    pub available: bool,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+/// `GET /api/converters` 回應
+#[derive(Debug, Deserialize)]
+struct ConvertersResponse {
+    converters: Vec<BackendConverter>,
+}
+
+/// 從後端取得轉換器清單
+pub async fn fetch_converters(
+    client: &reqwest::Client,
+    backend_url: &str,
+) -> Result<Vec<BackendConverter>, ApiError> {
+    let url = format!("{}/api/converters", backend_url);
+
+    let response = client
+        .get(&url)
+        .timeout(SYNC_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| ApiError::BackendError(format!("Backend unreachable: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(ApiError::BackendError(format!(
+            "Converter listing failed: HTTP {}",
+            response.status()
+        )));
+    }
+
+    let body: ConvertersResponse = response
+        .json()
+        .await
+        .map_err(|e| ApiError::BackendError(format!("Invalid converter listing: {}", e)))?;
+    Ok(body.converters)
+}
+
+/// 從後端同步一次引擎清單
+///
+/// 後端無法連線或回報空清單時回傳錯誤，註冊表維持原狀。
+pub async fn sync_engines(
+    registry: &EngineRegistry,
+    client: &reqwest::Client,
+    backend_url: &str,
+) -> Result<CatalogDiff, ApiError> {
+    let converters = fetch_converters(client, backend_url).await?;
+    if converters.is_empty() {
+        return Err(ApiError::BackendError(
+            "Backend advertised no converters".to_string(),
+        ));
+    }
+    Ok(registry.apply_backend_converters(converters).await)
+}
+
+/// 啟動定期同步（啟動時立即同步一次）；同步間隔為 0 時不啟動
+pub fn start(
+    registry: EngineRegistry,
+    backend_url: String,
+    interval: Duration,
+) -> Option<JoinHandle<()>> {
+    if interval.is_zero() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+            match sync_engines(&registry, &client, &backend_url).await {
+                Ok(diff) => log_diff("backend", &diff),
+                Err(e) => warn!("⚠️ Engine sync failed, keeping current engines: {}", e),
+            }
+        }
+    }))
+}
+
+/// 合併本地引擎清單與後端回報的轉換器
+pub fn merge_engines(catalog: &[Engine], converters: &[BackendConverter]) -> Vec<Engine> {
+    let advertised: BTreeMap<String, &BackendConverter> = converters
+        .iter()
+        .map(|c| (engine_key(&c.name), c))
+        .filter(|(key, _)| !key.is_empty())
+        .collect();
+
+    let mut engines: Vec<Engine> = catalog
+        .iter()
+        .map(
+            |engine| match advertised.get(&engine_key(&engine.engine_id)) {
+                Some(converter) => apply_converter(engine.clone(), converter),
+                None => Engine {
+                    available: false,
+                    ..engine.clone()
+                },
+            },
+        )
+        .collect();
+
+    for (key, converter) in &advertised {
+        if catalog.iter().any(|e| &engine_key(&e.engine_id) == key) {
+            continue;
+        }
+        if is_valid_engine_id(key) {
+            engines.push(discovered_engine(key, converter));
+        } else {
+            warn!(
+                "⚠️ Ignoring backend converter with unusable name: {:?}",
+                converter.name
+            );
+        }
+    }
+
+    engines
+}
+
+/// 比對引擎用的名稱：小寫且只保留英數字
+fn engine_key(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// 以後端回報的格式更新本地引擎
+fn apply_converter(mut engine: Engine, converter: &BackendConverter) -> Engine {
+    engine.input_formats = normalize_formats(&converter.inputs);
+    engine.output_formats = normalize_formats(&converter.outputs);
+    engine.available = converter.available;
+
+    if !engine.capabilities.is_empty() {
+        let inputs = &engine.input_formats;
+        let outputs = &engine.output_formats;
+        engine.capabilities.retain_mut(|c| {
+            c.output_formats.retain(|f| outputs.contains(f));
+            inputs.contains(&c.input_format) && !c.output_formats.is_empty()
+        });
+        // 本地限定的轉換組合後端都不再支援時，不可退回為所有組合皆可轉換
+        if engine.capabilities.is_empty() {
+            engine.available = false;
+        }
+    }
+
+    engine
+}
+
+/// 只存在於後端的引擎
+fn discovered_engine(engine_id: &str, converter: &BackendConverter) -> Engine {
+    Engine {
+        engine_id: engine_id.to_string(),
+        engine_name: converter.name.clone(),
+        description: String::new(),
+        enabled: true,
+        available: converter.available,
+        input_formats: normalize_formats(&converter.inputs),
+        output_formats: normalize_formats(&converter.outputs),
+        capabilities: Vec::new(),
+        max_file_size_mb: DISCOVERED_MAX_FILE_SIZE_MB,
+        timeout_secs: None,
+        priority: 0,
+        conversion_priorities: BTreeMap::new(),
+        requires_params: false,
+        params_schema: None,
+    }
+}
+
+/// 統一格式名稱並去除重複
+fn normalize_formats(formats: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::with_capacity(formats.len());
+    for format in formats.iter().map(|f| normalize_format(f)) {
+        if !format.is_empty() && !result.contains(&format) {
+            result.push(format);
+        }
+    }
+    result
+}
@@ -0,0 +1,94 @@
+//! 上傳檔案暫存模組
+//!
+//! 上傳內容逐塊寫入 `upload_dir` 下的暫存檔，不在記憶體中保留整個檔案：
+//! - 寫入期間即檢查大小上限，超過時立即中止
+//! - 保留檔案開頭供格式偵測使用
+//! - 任務建立後以 rename 原子地移到任務目錄，失敗時暫存檔自動刪除
+
+use axum::extract::multipart::{Field, MultipartError};
+use axum::http::StatusCode;
+use std::path::Path;
+use tempfile::TempPath;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::ApiError;
+use crate::sniff::SNIFF_LEN;
+
+/// 暫存檔名前綴
+pub const STAGING_PREFIX: &str = ".upload-";
+
+/// 寫入暫存檔的上傳檔案
+#[derive(Debug)]
+pub struct StagedUpload {
+    path: TempPath,
+    /// 檔案大小（bytes）
+    pub size: u64,
+    /// 檔案開頭（最多 `SNIFF_LEN` bytes）
+    pub head: Vec<u8>,
+}
+
+impl StagedUpload {
+    /// 將暫存檔移到指定位置
+    pub async fn persist(self, dest: &Path) -> Result<(), ApiError> {
+        let dest = dest.to_path_buf();
+        tokio::task::spawn_blocking(move || self.path.persist(dest))
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to move upload: {}", e)))?
+            .map_err(|e| ApiError::InternalError(format!("Failed to move upload: {}", e.error)))
+    }
+}
+
+/// 將 multipart 檔案欄位寫入 `dir` 下的暫存檔
+///
+/// 累計大小超過 `max_size` 時回傳 `FileTooLarge(max_size)`，已寫入的內容隨之刪除。
+pub async fn stage_upload(
+    field: &mut Field<'_>,
+    dir: &Path,
+    max_size: u64,
+) -> Result<StagedUpload, ApiError> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to create upload dir: {}", e)))?;
+
+    let dir = dir.to_path_buf();
+    let temp = tokio::task::spawn_blocking(move || {
+        tempfile::Builder::new()
+            .prefix(STAGING_PREFIX)
+            .tempfile_in(dir)
+    })
+    .await
+    .map_err(|e| ApiError::InternalError(format!("Failed to create file: {}", e)))?
+    .map_err(|e| ApiError::InternalError(format!("Failed to create file: {}", e)))?;
+    let (file, path) = temp.into_parts();
+    let mut file = File::from_std(file);
+
+    let mut size: u64 = 0;
+    let mut head = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(|e| read_error(e, max_size))? {
+        size += chunk.len() as u64;
+        if size > max_size {
+            return Err(ApiError::FileTooLarge(max_size));
+        }
+        if head.len() < SNIFF_LEN {
+            let take = chunk.len().min(SNIFF_LEN - head.len());
+            head.extend_from_slice(&chunk[..take]);
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to write file: {}", e)))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to write file: {}", e)))?;
+
+    Ok(StagedUpload { path, size, head })
+}
+
+fn read_error(error: MultipartError, max_size: u64) -> ApiError {
+    if error.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::FileTooLarge(max_size)
+    } else {
+        ApiError::InvalidInput(format!("Failed to read file: {}", error))
+    }
+}
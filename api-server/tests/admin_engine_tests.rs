@@ -0,0 +1,259 @@
+//! Engine administration tests
+
+use axum::http::{header, StatusCode};
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::engine::{default_engines, EngineOverride};
+use convertx_api::sync::BackendConverter;
+use convertx_api::{build_router, EngineRegistry, JobStatus};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{
+    create_test_state, create_test_state_with_backend, generate_scoped_token,
+    start_hanging_backend, start_runner, wait_for_status,
+};
+
+fn disable() -> EngineOverride {
+    EngineOverride {
+        enabled: Some(false),
+        ..Default::default()
+    }
+}
+
+fn convert_form(filename: &str, params: Value) -> MultipartForm {
+    MultipartForm::new()
+        .add_part("file", Part::bytes(b"data".to_vec()).file_name(filename))
+        .add_text("params", params.to_string())
+}
+
+mod registry_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_engine() {
+        let registry = EngineRegistry::new();
+
+        let engine = registry
+            .update_engine(
+                "ffmpeg",
+                EngineOverride {
+                    max_file_size_mb: Some(10),
+                    timeout_secs: Some(30),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(engine.enabled);
+        assert_eq!(engine.max_file_size_mb, 10);
+        assert_eq!(engine.timeout_secs, Some(30));
+
+        // 後續的調整只覆蓋有指定的欄位
+        let engine = registry.update_engine("ffmpeg", disable()).await.unwrap();
+        assert!(!engine.enabled);
+        assert_eq!(engine.max_file_size_mb, 10);
+        assert!(!registry.is_engine_available("ffmpeg").await);
+        assert!(registry
+            .find_engine_for_conversion("mp4", "webm", 0)
+            .await
+            .is_none());
+
+        assert!(registry.update_engine("nope", disable()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_overrides_survive_reload_and_sync() {
+        let registry = EngineRegistry::new();
+        registry.update_engine("pandoc", disable()).await.unwrap();
+
+        registry.replace_engines(default_engines()).await;
+        assert!(!registry.get_engine("pandoc").await.unwrap().enabled);
+
+        registry
+            .apply_backend_converters(vec![BackendConverter {
+                name: "pandoc".to_string(),
+                inputs: vec!["md".to_string()],
+                outputs: vec!["html".to_string()],
+                available: true,
+            }])
+            .await;
+        let pandoc = registry.get_engine("pandoc").await.unwrap();
+        assert!(!pandoc.enabled);
+        assert_eq!(pandoc.input_formats, vec!["md"]);
+
+        registry
+            .update_engine(
+                "pandoc",
+                EngineOverride {
+                    enabled: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(registry.is_engine_available("pandoc").await);
+    }
+}
+
+mod rest_admin_tests {
+    use super::*;
+
+    async fn patch_engine(
+        server: &TestServer,
+        engine_id: &str,
+        scope: &[&str],
+        body: Value,
+    ) -> (StatusCode, Value) {
+        let response = server
+            .patch(&format!("/api/v1/admin/engines/{}", engine_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_scoped_token("admin-1", scope)),
+            )
+            .json(&body)
+            .await;
+        (response.status_code(), response.json())
+    }
+
+    #[tokio::test]
+    async fn test_admin_updates_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        let (status, body) = patch_engine(
+            &server,
+            "ffmpeg",
+            &["admin"],
+            json!({ "enabled": false, "max_file_size_mb": 50, "timeout_secs": 120 }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let engine = &body["data"]["engine"];
+        assert_eq!(engine["engine_id"], "ffmpeg");
+        assert_eq!(engine["enabled"], false);
+        assert_eq!(engine["max_file_size_mb"], 50);
+        assert_eq!(engine["timeout_secs"], 120);
+
+        let response = server
+            .get("/api/v1/engines/ffmpeg")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_scoped_token("user-1", &[])),
+            )
+            .await;
+        let body: Value = response.json();
+        assert_eq!(body["data"]["engine"]["enabled"], false);
+    }
+
+    #[tokio::test]
+    async fn test_admin_scope_required() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        for scope in [&[][..], &["convert", "read"][..]] {
+            let (status, body) =
+                patch_engine(&server, "ffmpeg", scope, json!({ "enabled": false })).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert_eq!(body["code"], "FORBIDDEN");
+        }
+
+        let (status, _) = patch_engine(&server, "ffmpeg", &["*"], json!({ "enabled": true })).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_updates_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        let (status, body) =
+            patch_engine(&server, "nope", &["admin"], json!({ "enabled": false })).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "ENGINE_NOT_FOUND");
+
+        for invalid in [
+            json!({}),
+            json!({ "max_file_size_mb": 0 }),
+            json!({ "timeout_secs": 0 }),
+            json!({ "enabled": "no" }),
+            json!({ "description": "renamed" }),
+        ] {
+            let (status, body) = patch_engine(&server, "ffmpeg", &["admin"], invalid).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["code"], "INVALID_INPUT");
+        }
+    }
+}
+
+mod disabled_engine_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_explicit_disabled_engine_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .update_engine("ffmpeg", disable())
+            .await
+            .unwrap();
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_scoped_token("user-1", &[])),
+            )
+            .multipart(convert_form(
+                "clip.mp4",
+                json!({ "output_format": "webm", "engine_id": "ffmpeg" }),
+            ))
+            .await;
+
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        assert_eq!(body["code"], "ENGINE_DISABLED");
+    }
+
+    #[tokio::test]
+    async fn test_engine_timeout_applied() {
+        let backend_url = start_hanging_backend(Default::default()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        state
+            .engine_registry
+            .update_engine(
+                "ffmpeg",
+                EngineOverride {
+                    timeout_secs: Some(1),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        start_runner(&state);
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_scoped_token("user-1", &[])),
+            )
+            .multipart(convert_form(
+                "clip.mp4",
+                json!({ "output_format": "webm", "engine_id": "ffmpeg" }),
+            ))
+            .await;
+        response.assert_status_ok();
+        let body: Value = response.json();
+        let job_id = body["data"]["job_id"].as_str().unwrap().to_string();
+
+        wait_for_status(&state, &job_id, JobStatus::Failed).await;
+    }
+}
@@ -0,0 +1,273 @@
+//! Backend conversion request tests
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{DefaultBodyLimit, Multipart};
+use axum::http::header;
+use axum::response::Response;
+use axum::routing::post;
+use axum::Router;
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::{build_router, AppState, JobStatus};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+mod common;
+
+use common::{
+    create_test_state_with_backend, generate_token, serve_backend, start_runner, wait_for_finish,
+    wait_for_job,
+};
+
+/// Text fields received by the backend stub, one map per request.
+type Received = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+/// Start a backend stub that records the form fields of each convert request.
+async fn start_recording_backend() -> (String, Received) {
+    let received: Received = Arc::new(Mutex::new(Vec::new()));
+    let recorder = received.clone();
+    let app = Router::new().route(
+        "/api/convert",
+        post(move |mut multipart: Multipart| {
+            let recorder = recorder.clone();
+            async move {
+                let mut fields = HashMap::new();
+                while let Some(field) = multipart.next_field().await.unwrap() {
+                    let name = field.name().unwrap_or_default().to_string();
+                    if name != "file" {
+                        fields.insert(name, field.text().await.unwrap());
+                    }
+                }
+                recorder.lock().unwrap().push(fields);
+                "converted"
+            }
+        }),
+    );
+    (serve_backend(app).await, received)
+}
+
+/// Start a backend stub that answers every convert request with `respond`.
+async fn start_backend<F, Fut>(respond: F) -> String
+where
+    F: Fn(Vec<u8>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: std::future::Future<Output = Response> + Send,
+{
+    let app = Router::new()
+        .route(
+            "/api/convert",
+            post(move |mut multipart: Multipart| {
+                let respond = respond.clone();
+                async move {
+                    let mut file = Vec::new();
+                    while let Some(field) = multipart.next_field().await.unwrap() {
+                        if field.name() == Some("file") {
+                            file = field.bytes().await.unwrap().to_vec();
+                        }
+                    }
+                    respond(file).await
+                }
+            }),
+        )
+        .layer(DefaultBodyLimit::disable());
+    serve_backend(app).await
+}
+
+/// Submit a conversion and return its job ID.
+async fn submit(state: &AppState, filename: &str, data: Vec<u8>, params: Value) -> String {
+    let server = TestServer::new(build_router(state.clone())).unwrap();
+    let form = MultipartForm::new()
+        .add_part("file", Part::bytes(data).file_name(filename))
+        .add_text("params", params.to_string());
+
+    let response = server
+        .post("/api/v1/convert")
+        .add_header(
+            header::AUTHORIZATION,
+            format!("Bearer {}", generate_token("user-1")),
+        )
+        .multipart(form)
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    body["data"]["job_id"].as_str().unwrap().to_string()
+}
+
+/// Submit a conversion and wait for it to complete.
+async fn convert(state: &AppState, filename: &str, params: Value) {
+    let job_id = submit(state, filename, b"data".to_vec(), params).await;
+    let job = wait_for_finish(state, &job_id).await;
+    assert_eq!(job.status, JobStatus::Completed, "{:?}", job.error_message);
+}
+
+mod forwarding_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chosen_engine_forwarded() {
+        let (backend_url, received) = start_recording_backend().await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        start_runner(&state);
+
+        for engine_id in ["graphicsmagick", "imagemagick"] {
+            convert(
+                &state,
+                "photo.png",
+                json!({ "output_format": "jpg", "engine_id": engine_id }),
+            )
+            .await;
+        }
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["engine"], "graphicsmagick");
+        assert_eq!(received[1]["engine"], "imagemagick");
+        assert_eq!(received[0]["targetFormat"], "jpg");
+        assert!(!received[0].contains_key("options"));
+    }
+
+    #[tokio::test]
+    async fn test_options_forwarded_as_json() {
+        let (backend_url, received) = start_recording_backend().await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        start_runner(&state);
+
+        convert(
+            &state,
+            "paper.pdf",
+            json!({
+                "output_format": "md-t",
+                "engine_id": "mineru",
+                "options": { "method": "ocr" }
+            }),
+        )
+        .await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["engine"], "mineru");
+        let options: Value = serde_json::from_str(&received[0]["options"]).unwrap();
+        assert_eq!(options, json!({ "method": "ocr" }));
+    }
+
+    #[tokio::test]
+    async fn test_auto_selected_engine_forwarded() {
+        let (backend_url, received) = start_recording_backend().await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        start_runner(&state);
+
+        convert(&state, "clip.mp4", json!({ "output_format": "webm" })).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["engine"], "ffmpeg");
+    }
+}
+
+mod streaming_tests {
+    use super::*;
+
+    const MB: usize = 1024 * 1024;
+
+    fn params() -> Value {
+        json!({ "output_format": "jpg", "engine_id": "graphicsmagick" })
+    }
+
+    #[tokio::test]
+    async fn test_large_file_round_trip() {
+        // 後端將收到的內容反轉後回傳
+        let backend_url = start_backend(|mut file: Vec<u8>| async move {
+            file.reverse();
+            Response::new(Body::from(file))
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        start_runner(&state);
+
+        let content: Vec<u8> = (0..3 * MB).map(|i| (i % 251) as u8).collect();
+        let job_id = submit(&state, "photo.png", content.clone(), params()).await;
+        let job = wait_for_finish(&state, &job_id).await;
+        assert_eq!(job.status, JobStatus::Completed, "{:?}", job.error_message);
+
+        let output = std::fs::read(job.output_file.unwrap()).unwrap();
+        let mut expected = content;
+        expected.reverse();
+        assert_eq!(output, expected);
+
+        // 完成時已記錄輸出的 SHA-256
+        let checksum: String = Sha256::digest(&output)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(job.output_checksum, Some(checksum));
+    }
+
+    #[tokio::test]
+    async fn test_progress_follows_download() {
+        // 分段回傳並附上 Content-Length，下載期間進度應逐步推進
+        let backend_url = start_backend(|_| async {
+            let chunks = futures::stream::iter(0..4).then(|_| async {
+                tokio::time::sleep(Duration::from_millis(150)).await;
+                Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; 1024]))
+            });
+            Response::builder()
+                .header(header::CONTENT_LENGTH, 4 * 1024)
+                .body(Body::from_stream(chunks))
+                .unwrap()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        start_runner(&state);
+
+        let job_id = submit(&state, "photo.png", b"data".to_vec(), params()).await;
+        let mut seen = Vec::new();
+        wait_for_job(&state, &job_id, |job| {
+            seen.push(job.progress);
+            job.status == JobStatus::Completed
+        })
+        .await;
+
+        assert!(seen.windows(2).all(|w| w[0] <= w[1]), "{:?}", seen);
+        assert!(seen.iter().any(|p| *p > 50 && *p < 90), "{:?}", seen);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_download_leaves_no_output() {
+        // 宣告的長度與實際內容不符，連線在傳送途中中斷
+        let backend_url = start_backend(|_| async {
+            let chunks = futures::stream::iter(0..2).then(|i| async move {
+                if i == 0 {
+                    return Ok(Bytes::from(vec![b'x'; 1024]));
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Err(std::io::Error::other("backend crashed"))
+            });
+            Response::builder()
+                .header(header::CONTENT_LENGTH, 1024 * 1024)
+                .body(Body::from_stream(chunks))
+                .unwrap()
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        start_runner(&state);
+
+        let job_id = submit(&state, "photo.png", b"data".to_vec(), params()).await;
+        let job = wait_for_finish(&state, &job_id).await;
+        assert_eq!(job.status, JobStatus::Failed);
+        let message = job.error_message.unwrap();
+        assert!(message.contains("Failed to read response"), "{}", message);
+        assert!(!dir
+            .path()
+            .join("output")
+            .join(&job_id)
+            .join("photo.jpg")
+            .exists());
+    }
+}
@@ -0,0 +1,259 @@
+//! Job cancellation tests
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::http::{header, StatusCode};
+use axum_test::TestServer;
+use convertx_api::{build_router, handlers, AppState, JobPriority, JobStatus, QueuedJob};
+use serde_json::Value;
+
+mod common;
+
+use common::{
+    create_test_state, create_test_state_with_backend, generate_token, sample_job,
+    start_hanging_backend, start_runner, wait_for_status, wait_until,
+};
+
+mod store_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancelled_job_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+
+        let cancelled = state.job_store.cancel_job(&job.job_id).await.unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+
+        assert!(state
+            .job_store
+            .complete_job(&job.job_id, "/tmp/out.jpg".to_string())
+            .await
+            .is_none());
+        assert!(state
+            .job_store
+            .update_status(&job.job_id, JobStatus::Processing)
+            .await
+            .is_none());
+
+        let job = state.job_store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert!(job.output_file.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_finished_job_cannot_be_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        state
+            .job_store
+            .complete_job(&job.job_id, "/tmp/out.jpg".to_string())
+            .await;
+
+        assert!(state.job_store.cancel_job(&job.job_id).await.is_none());
+    }
+}
+
+mod queue_cancel_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_pending_removes_from_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        state
+            .job_queue
+            .enqueue(QueuedJob {
+                job_id: "job-1".to_string(),
+                engine_ids: vec!["ffmpeg".to_string()],
+                user_id: "user-1".to_string(),
+                priority: JobPriority::Normal,
+            })
+            .await
+            .unwrap();
+
+        assert!(state.job_queue.cancel("job-1").await);
+        assert_eq!(state.job_queue.pending_count().await, 0);
+        assert_eq!(state.job_queue.user_in_flight("user-1").await, 0);
+        assert!(!state.job_queue.cancel("job-1").await);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_running_backend_request() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let backend_url = start_hanging_backend(hits.clone()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        let upload_dir = dir.path().join("uploads").join(&job.job_id);
+        std::fs::create_dir_all(&upload_dir).unwrap();
+        std::fs::write(upload_dir.join("photo.png"), b"fake png").unwrap();
+
+        start_runner(&state);
+        state
+            .job_queue
+            .enqueue(QueuedJob::for_job(&job))
+            .await
+            .unwrap();
+
+        wait_for_status(&state, &job.job_id, JobStatus::Processing).await;
+        let hits_ref = &hits;
+        wait_until("the backend request", || async move {
+            hits_ref.load(Ordering::SeqCst) > 0
+        })
+        .await;
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let cancelled = handlers::request_cancellation(&state, "user-1", &job.job_id)
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+
+        // The worker slot is released well before the backend would answer
+        let queue = &state.job_queue;
+        wait_until("the worker slot", || async move {
+            queue.running_count().await == 0
+        })
+        .await;
+        assert!(!dir.path().join("output").join(&job.job_id).exists());
+
+        let job = state.job_store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+    }
+}
+
+mod rest_cancel_tests {
+    use super::*;
+
+    fn create_server(state: AppState) -> TestServer {
+        TestServer::new(build_router(state)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cancel_pending_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        let server = create_server(state.clone());
+
+        let response = server
+            .post(&format!("/api/v1/jobs/{}/cancel", job.job_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["data"]["status"], "cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_requires_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        let server = create_server(state.clone());
+
+        let response = server
+            .post(&format!("/api/v1/jobs/{}/cancel", job.job_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("someone-else")),
+            )
+            .await;
+
+        response.assert_status(StatusCode::FORBIDDEN);
+        let job = state.job_store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_finished_job_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        state
+            .job_store
+            .fail_job(&job.job_id, "backend error".to_string())
+            .await;
+        let server = create_server(state);
+
+        let response = server
+            .post(&format!("/api/v1/jobs/{}/cancel", job.job_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status(StatusCode::CONFLICT);
+        let body: Value = response.json();
+        assert_eq!(body["code"], "JOB_ALREADY_FINISHED");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_missing_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let server = create_server(state);
+
+        let response = server
+            .post("/api/v1/jobs/00000000-0000-0000-0000-000000000000/cancel")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_requires_auth() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        let server = create_server(state);
+
+        let response = server
+            .post(&format!("/api/v1/jobs/{}/cancel", job.job_id))
+            .await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+}
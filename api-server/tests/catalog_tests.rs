@@ -0,0 +1,380 @@
+//! Engine catalog loading tests
+
+use convertx_api::catalog::{
+    load_catalog, load_engine_registry, parse_catalog, reload_catalog, start_reloader,
+    validate_engines, CatalogDiff, CatalogError, CatalogFormat,
+};
+use convertx_api::engine::default_engines;
+use convertx_api::{AppConfig, EngineRegistry};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const MINIMAL_TOML: &str = r#"
+[[engines]]
+engine_id = "pandoc"
+engine_name = "Pandoc"
+input_formats = [".MD", "html"]
+output_formats = ["pdf"]
+max_file_size_mb = 10
+"#;
+
+fn config_with_path(path: Option<String>) -> AppConfig {
+    AppConfig {
+        engine_config_path: path,
+        ..Default::default()
+    }
+}
+
+fn validation_errors(result: Result<Vec<convertx_api::Engine>, CatalogError>) -> Vec<String> {
+    match result {
+        Err(CatalogError::Invalid(errors)) => errors,
+        other => panic!(
+            "expected validation errors, got {:?}",
+            other.map(|e| e.len())
+        ),
+    }
+}
+
+mod catalog_tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_engines_are_valid() {
+        assert!(validate_engines(&default_engines()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_toml_with_defaults_and_normalization() {
+        let engines = parse_catalog(MINIMAL_TOML, CatalogFormat::Toml).unwrap();
+        assert_eq!(engines.len(), 1);
+
+        let engine = &engines[0];
+        assert!(engine.enabled);
+        assert!(!engine.requires_params);
+        assert_eq!(engine.input_formats, vec!["md", "html"]);
+        assert!(engine.supports_conversion("md", "pdf"));
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = r#"{
+            "engines": [{
+                "engine_id": "vcf",
+                "engine_name": "VCF",
+                "enabled": false,
+                "input_formats": ["vcf"],
+                "output_formats": ["csv"],
+                "max_file_size_mb": 1,
+                "params_schema": { "type": "object" }
+            }]
+        }"#;
+
+        let engines = parse_catalog(json, CatalogFormat::Json).unwrap();
+        assert!(!engines[0].enabled);
+        assert!(engines[0].params_schema.is_some());
+    }
+
+    #[test]
+    fn test_example_catalog_is_valid() {
+        let example = include_str!("../engines.example.toml");
+        let engines = parse_catalog(example, CatalogFormat::Toml).unwrap();
+
+        let libjxl = engines.iter().find(|e| e.engine_id == "libjxl").unwrap();
+        assert!(libjxl.supports_conversion("png", "jxl"));
+        assert!(!libjxl.supports_conversion("png", "jpeg"));
+
+        let mineru = engines.iter().find(|e| e.engine_id == "mineru").unwrap();
+        assert!(mineru.params_schema.as_ref().unwrap()["properties"]["method"].is_object());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let result = parse_catalog("[[engines]]\nengine_id = 1", CatalogFormat::Toml);
+        assert!(matches!(result, Err(CatalogError::Parse(_))));
+
+        let result = parse_catalog("{}", CatalogFormat::Json);
+        assert!(matches!(result, Err(CatalogError::Parse(_))));
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let toml = r#"
+[[engines]]
+engine_id = "dup"
+engine_name = "A"
+input_formats = ["a"]
+output_formats = ["b"]
+max_file_size_mb = 0
+
+[[engines]]
+engine_id = "dup"
+engine_name = "B"
+input_formats = ["a"]
+output_formats = []
+max_file_size_mb = 1
+
+[[engines]]
+engine_id = "Bad Id"
+engine_name = "C"
+input_formats = ["a"]
+output_formats = ["b"]
+max_file_size_mb = 1
+params_schema = "not an object"
+
+[[engines.capabilities]]
+input_format = "z"
+output_formats = ["b", "y"]
+"#;
+
+        let errors = validation_errors(parse_catalog(toml, CatalogFormat::Toml));
+        let joined = errors.join("\n");
+        assert!(joined.contains("max_file_size_mb"));
+        assert!(joined.contains("重複"));
+        assert!(joined.contains("output_formats 不可為空"));
+        assert!(joined.contains("engine_id 只能包含"));
+        assert!(joined.contains("輸入格式 z"));
+        assert!(joined.contains("輸出格式 y"));
+        assert!(joined.contains("params_schema"));
+        assert_eq!(errors.len(), 7);
+    }
+
+    #[test]
+    fn test_empty_catalog_rejected() {
+        let errors = validation_errors(parse_catalog(r#"{"engines": []}"#, CatalogFormat::Json));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            CatalogFormat::from_path(Path::new("e.TOML")),
+            CatalogFormat::Toml
+        );
+        assert_eq!(
+            CatalogFormat::from_path(Path::new("e.json")),
+            CatalogFormat::Json
+        );
+    }
+}
+
+mod registry_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_builtin_engines_without_config() {
+        let registry = load_engine_registry(&config_with_path(None)).unwrap();
+        assert!(registry.get_engine("ffmpeg").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_falls_back_to_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join("missing.toml")
+            .to_string_lossy()
+            .to_string();
+
+        let registry = load_engine_registry(&config_with_path(Some(path))).unwrap();
+        assert_eq!(registry.list_engines().await.len(), default_engines().len());
+    }
+
+    #[tokio::test]
+    async fn test_registry_loaded_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.toml");
+        std::fs::write(&path, MINIMAL_TOML).unwrap();
+
+        let registry =
+            load_engine_registry(&config_with_path(Some(path.to_string_lossy().to_string())))
+                .unwrap();
+        let engines = registry.list_engines().await;
+        assert_eq!(engines.len(), 1);
+        assert_eq!(engines[0].engine_id, "pandoc");
+        assert!(registry.get_engine("ffmpeg").await.is_none());
+    }
+
+    #[test]
+    fn test_invalid_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.json");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let err = load_catalog(&path).unwrap_err();
+        assert!(err.to_string().contains("engines.json"));
+
+        let result =
+            load_engine_registry(&config_with_path(Some(path.to_string_lossy().to_string())));
+        assert!(result.is_err());
+    }
+}
+
+/// 寫入設定檔並推進修改時間，避免檔案系統時間精度造成漏偵測
+fn write_catalog(path: &Path, content: &str, modified: SystemTime) {
+    std::fs::write(path, content).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+}
+
+mod reload_tests {
+    use super::*;
+
+    const UPDATED_TOML: &str = r#"
+[[engines]]
+engine_id = "pandoc"
+engine_name = "Pandoc"
+enabled = false
+input_formats = ["md", "rst"]
+output_formats = ["pdf"]
+max_file_size_mb = 10
+
+[[engines]]
+engine_id = "vcf"
+engine_name = "VCF"
+input_formats = ["vcf"]
+output_formats = ["csv"]
+max_file_size_mb = 1
+"#;
+
+    #[test]
+    fn test_diff_reports_engines_and_formats() {
+        let old = parse_catalog(MINIMAL_TOML, CatalogFormat::Toml).unwrap();
+        let new = parse_catalog(UPDATED_TOML, CatalogFormat::Toml).unwrap();
+
+        let diff = CatalogDiff::between(&old, &new);
+        assert_eq!(diff.added, vec!["vcf"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+
+        let change = &diff.changed[0];
+        assert_eq!(change.enabled, Some(false));
+        assert_eq!(change.added_input_formats, vec!["rst"]);
+        assert_eq!(change.removed_input_formats, vec!["html"]);
+        assert!(change.added_output_formats.is_empty());
+        assert_eq!(
+            change.to_string(),
+            "pandoc: disabled; +input [rst]; -input [html]"
+        );
+
+        let reverse = CatalogDiff::between(&new, &old);
+        assert_eq!(reverse.removed, vec!["vcf"]);
+        assert!(CatalogDiff::between(&old, &old).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reload_swaps_engines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.toml");
+        std::fs::write(&path, UPDATED_TOML).unwrap();
+
+        let registry =
+            EngineRegistry::with_engines(parse_catalog(MINIMAL_TOML, CatalogFormat::Toml).unwrap());
+        let diff = reload_catalog(&registry, &path).await.unwrap();
+        assert_eq!(diff.added, vec!["vcf"]);
+
+        assert!(!registry.is_engine_available("pandoc").await);
+        assert!(registry.is_engine_available("vcf").await);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_reload_keeps_current_engines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.toml");
+        std::fs::write(&path, "[[engines]]\nengine_id = \"broken\"").unwrap();
+
+        let registry =
+            EngineRegistry::with_engines(parse_catalog(MINIMAL_TOML, CatalogFormat::Toml).unwrap());
+        assert!(reload_catalog(&registry, &path).await.is_err());
+
+        let engines = registry.list_engines().await;
+        assert_eq!(engines.len(), 1);
+        assert!(engines[0].enabled);
+    }
+
+    #[tokio::test]
+    async fn test_reloader_picks_up_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.toml");
+        let start = SystemTime::now() - Duration::from_secs(60);
+        write_catalog(&path, MINIMAL_TOML, start);
+
+        let registry =
+            load_engine_registry(&config_with_path(Some(path.to_string_lossy().to_string())))
+                .unwrap();
+        let handle = start_reloader(registry.clone(), path.clone(), Duration::from_millis(20));
+
+        // 無效的設定被拒絕，保留原本的引擎
+        write_catalog(&path, "not = [valid", start + Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(registry.is_engine_available("pandoc").await);
+
+        write_catalog(&path, UPDATED_TOML, start + Duration::from_secs(2));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!registry.is_engine_available("pandoc").await);
+        assert!(registry.is_engine_available("vcf").await);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_reloader_retries_after_rejected_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.toml");
+        let start = SystemTime::now() - Duration::from_secs(60);
+        write_catalog(&path, MINIMAL_TOML, start);
+
+        let registry =
+            load_engine_registry(&config_with_path(Some(path.to_string_lossy().to_string())))
+                .unwrap();
+        let handle = start_reloader(registry.clone(), path.clone(), Duration::from_millis(20));
+
+        write_catalog(&path, "not = [valid", start + Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(registry.is_engine_available("pandoc").await);
+
+        // 修正後的檔案修改時間與無效的版本相同，大小不同仍會重新載入
+        write_catalog(&path, UPDATED_TOML, start + Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!registry.is_engine_available("pandoc").await);
+        assert!(registry.is_engine_available("vcf").await);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_reloader_parses_rejected_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engines.toml");
+        let start = SystemTime::now() - Duration::from_secs(60);
+        write_catalog(&path, MINIMAL_TOML, start);
+
+        let registry =
+            load_engine_registry(&config_with_path(Some(path.to_string_lossy().to_string())))
+                .unwrap();
+        let handle = start_reloader(registry.clone(), path.clone(), Duration::from_millis(20));
+
+        // 無效的版本與之後的有效內容大小相同
+        let invalid = format!("{:<width$}", "not = [valid", width = UPDATED_TOML.len());
+        write_catalog(&path, &invalid, start + Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(registry.is_engine_available("pandoc").await);
+
+        // 修改時間與大小都沒變，被拒絕的版本不會再被讀取，因此看不到新內容
+        write_catalog(&path, UPDATED_TOML, start + Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(registry.is_engine_available("pandoc").await);
+        assert!(!registry.is_engine_available("vcf").await);
+
+        // 修改時間變更後重新載入
+        write_catalog(&path, UPDATED_TOML, start + Duration::from_secs(2));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!registry.is_engine_available("pandoc").await);
+        assert!(registry.is_engine_available("vcf").await);
+
+        handle.abort();
+    }
+}
@@ -0,0 +1,200 @@
+//! Shared fixtures for the integration tests
+//!
+//! Each test binary uses only some of these helpers.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::routing::post;
+use axum::Router;
+use convertx_api::{create_schema, handlers, AppConfig, AppState, Engine, Job, JobStatus};
+use tempfile::TempDir;
+
+pub const TEST_SECRET: &str = "test-secret-key";
+
+/// Backend address nothing listens on, so conversions fail fast instead of hanging.
+pub const UNROUTABLE_BACKEND: &str = "http://127.0.0.1:1";
+
+/// Config with directories under `dir`, an in-memory database and an unroutable backend.
+pub fn test_config(dir: &TempDir) -> AppConfig {
+    AppConfig {
+        jwt_secret: TEST_SECRET.to_string(),
+        backend_url: UNROUTABLE_BACKEND.to_string(),
+        upload_dir: dir.path().join("uploads").to_string_lossy().to_string(),
+        output_dir: dir.path().join("output").to_string_lossy().to_string(),
+        database_path: ":memory:".to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn create_test_state(dir: &TempDir) -> AppState {
+    create_test_state_with(dir, |_| {})
+}
+
+/// State built from `test_config` after `configure` overrides individual fields.
+pub fn create_test_state_with(dir: &TempDir, configure: impl FnOnce(&mut AppConfig)) -> AppState {
+    let mut config = test_config(dir);
+    configure(&mut config);
+    AppState::new(config)
+}
+
+/// State that forwards conversions to `backend_url`.
+pub fn create_test_state_with_backend(dir: &TempDir, backend_url: &str) -> AppState {
+    create_test_state_with(dir, |config| config.backend_url = backend_url.to_string())
+}
+
+/// Attach the GraphQL schema so `/graphql` is served.
+pub fn with_graphql(state: AppState) -> AppState {
+    let schema = create_schema(state.clone());
+    state.with_graphql_schema(schema)
+}
+
+/// Run queued jobs the way the server does.
+pub fn start_runner(state: &AppState) {
+    let runner_state = state.clone();
+    state.job_queue.start(move |job_id, cancel| {
+        handlers::run_queued_job(runner_state.clone(), job_id, cancel)
+    });
+}
+
+/// Token for `user_id` without a scope claim (every non-admin operation allowed).
+pub fn generate_token(user_id: &str) -> String {
+    generate_scoped_token(user_id, &[])
+}
+
+/// Token for `user_id` carrying the given scopes.
+pub fn generate_scoped_token(user_id: &str, scope: &[&str]) -> String {
+    use chrono::Utc;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    #[derive(serde::Serialize)]
+    struct Claims {
+        sub: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        scope: Vec<String>,
+        exp: i64,
+        iat: i64,
+    }
+
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        scope: scope.iter().map(|s| s.to_string()).collect(),
+        exp: now + 3600,
+        iat: now,
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(TEST_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+/// Enabled engine converting any of `input_formats` to any of `output_formats`.
+///
+/// Tests override the fields they care about with struct update syntax.
+pub fn engine(engine_id: &str, input_formats: &[&str], output_formats: &[&str]) -> Engine {
+    Engine {
+        engine_id: engine_id.to_string(),
+        engine_name: engine_id.to_string(),
+        description: String::new(),
+        enabled: true,
+        available: true,
+        input_formats: input_formats.iter().map(|f| f.to_string()).collect(),
+        output_formats: output_formats.iter().map(|f| f.to_string()).collect(),
+        capabilities: Vec::new(),
+        max_file_size_mb: 10,
+        timeout_secs: None,
+        priority: 0,
+        conversion_priorities: Default::default(),
+        requires_params: false,
+        params_schema: None,
+    }
+}
+
+/// A pending png → jpg job owned by `user_id`, uploaded as `photo.png`.
+pub fn sample_job(user_id: &str) -> Job {
+    Job::new(
+        user_id.to_string(),
+        "photo.png".to_string(),
+        "png".to_string(),
+        "jpg".to_string(),
+        "imagemagick".to_string(),
+    )
+}
+
+/// Serve `app` on a free local port and return its base URL.
+pub async fn serve_backend(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// Backend stub whose convert endpoint never answers in time; `hits` counts the requests.
+pub async fn start_hanging_backend(hits: Arc<AtomicUsize>) -> String {
+    serve_backend(Router::new().route(
+        "/api/convert",
+        post(move || {
+            let hits = hits.clone();
+            async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                "too late"
+            }
+        }),
+    ))
+    .await
+}
+
+/// Poll `check` until it returns true, panicking with `what` after about four seconds.
+pub async fn wait_until<F, Fut>(what: &str, mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    for _ in 0..200 {
+        if check().await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("timed out waiting for {}", what);
+}
+
+/// Poll the job until `done` accepts it and return that state.
+pub async fn wait_for_job(
+    state: &AppState,
+    job_id: &str,
+    mut done: impl FnMut(&Job) -> bool,
+) -> Job {
+    for _ in 0..200 {
+        let job = state.job_store.get_job(job_id).await.unwrap();
+        if done(&job) {
+            return job;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let job = state.job_store.get_job(job_id).await.unwrap();
+    panic!("job {} stuck in {}", job_id, job.status);
+}
+
+/// Wait for the job to reach `status`.
+pub async fn wait_for_status(state: &AppState, job_id: &str, status: JobStatus) -> Job {
+    wait_for_job(state, job_id, |job| job.status == status).await
+}
+
+/// Wait for the job to complete or fail.
+pub async fn wait_for_finish(state: &AppState, job_id: &str) -> Job {
+    wait_for_job(state, job_id, |job| {
+        matches!(job.status, JobStatus::Completed | JobStatus::Failed)
+    })
+    .await
+}
@@ -0,0 +1,219 @@
+//! Job deletion tests
+
+use axum::http::{header, StatusCode};
+use axum_test::TestServer;
+use convertx_api::{build_router, AppState, Job, JobStore};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+mod common;
+
+use common::{create_test_state, generate_token, sample_job, with_graphql};
+
+/// Create a completed job with an upload, an output file and a download zip on disk.
+async fn create_job_with_files(state: &AppState, dir: &TempDir, user_id: &str) -> Job {
+    let job = state
+        .job_store
+        .create_job(sample_job(user_id))
+        .await
+        .unwrap();
+
+    let upload_dir = dir.path().join("uploads").join(&job.job_id);
+    std::fs::create_dir_all(&upload_dir).unwrap();
+    std::fs::write(upload_dir.join("photo.png"), b"fake png").unwrap();
+
+    let output_dir = dir.path().join("output").join(&job.job_id);
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let output_file = output_dir.join("photo.jpg");
+    std::fs::write(&output_file, b"fake jpg").unwrap();
+    std::fs::write(output_dir.join(format!("{}.zip", job.job_id)), b"zip").unwrap();
+
+    state
+        .job_store
+        .complete_job(&job.job_id, output_file.to_string_lossy().to_string())
+        .await
+        .unwrap()
+}
+
+fn job_dirs_exist(dir: &TempDir, job_id: &str) -> (bool, bool) {
+    (
+        dir.path().join("uploads").join(job_id).exists(),
+        dir.path().join("output").join(job_id).exists(),
+    )
+}
+
+mod store_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delete_job() {
+        let store = JobStore::new();
+        let job = store.create_job(sample_job("user-1")).await.unwrap();
+
+        let deleted = store.delete_job(&job.job_id).await.unwrap();
+        assert_eq!(deleted.job_id, job.job_id);
+        assert!(store.get_job(&job.job_id).await.is_none());
+        assert!(store.delete_job(&job.job_id).await.is_none());
+        assert!(store.get_user_jobs("user-1").await.is_empty());
+    }
+}
+
+mod rest_delete_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delete_removes_job_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let job = create_job_with_files(&state, &dir, "user-1").await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = server
+            .delete(&format!("/api/v1/jobs/{}", job.job_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["data"]["job_id"], job.job_id);
+        assert!(state.job_store.get_job(&job.job_id).await.is_none());
+        assert_eq!(job_dirs_exist(&dir, &job.job_id), (false, false));
+    }
+
+    #[tokio::test]
+    async fn test_delete_requires_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let job = create_job_with_files(&state, &dir, "user-1").await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = server
+            .delete(&format!("/api/v1/jobs/{}", job.job_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("someone-else")),
+            )
+            .await;
+
+        response.assert_status(StatusCode::FORBIDDEN);
+        assert!(state.job_store.get_job(&job.job_id).await.is_some());
+        assert_eq!(job_dirs_exist(&dir, &job.job_id), (true, true));
+    }
+
+    #[tokio::test]
+    async fn test_delete_pending_job_leaves_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        state
+            .job_queue
+            .enqueue(convertx_api::QueuedJob::for_job(&job))
+            .await
+            .unwrap();
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        server
+            .delete(&format!("/api/v1/jobs/{}", job.job_id))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await
+            .assert_status_ok();
+
+        assert_eq!(state.job_queue.pending_count().await, 0);
+        assert_eq!(state.job_queue.user_in_flight("user-1").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = server
+            .delete("/api/v1/jobs/00000000-0000-0000-0000-000000000000")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
+
+mod graphql_delete_tests {
+    use super::*;
+
+    fn delete_mutation(job_id: &str) -> Value {
+        json!({ "query": format!(r#"mutation {{ deleteJob(id: "{}") }}"#, job_id) })
+    }
+
+    #[tokio::test]
+    async fn test_graphql_delete_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let job = create_job_with_files(&state, &dir, "user-1").await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = server
+            .post("/graphql")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .json(&delete_mutation(&job.job_id))
+            .await;
+
+        let body: Value = response.json();
+        assert_eq!(body["data"]["deleteJob"], true);
+        assert!(state.job_store.get_job(&job.job_id).await.is_none());
+        assert_eq!(job_dirs_exist(&dir, &job.job_id), (false, false));
+    }
+
+    #[tokio::test]
+    async fn test_graphql_delete_rejects_other_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let job = create_job_with_files(&state, &dir, "user-1").await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = server
+            .post("/graphql")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("someone-else")),
+            )
+            .json(&delete_mutation(&job.job_id))
+            .await;
+
+        let body: Value = response.json();
+        assert!(body["errors"].is_array());
+        assert!(state.job_store.get_job(&job.job_id).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_graphql_delete_requires_auth() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let job = create_job_with_files(&state, &dir, "user-1").await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = server
+            .post("/graphql")
+            .json(&delete_mutation(&job.job_id))
+            .await;
+
+        let body: Value = response.json();
+        assert!(body["errors"].is_array());
+        assert!(state.job_store.get_job(&job.job_id).await.is_some());
+    }
+}
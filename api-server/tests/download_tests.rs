@@ -0,0 +1,526 @@
+//! Job result download tests
+
+use std::io::Read;
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use convertx_api::download::{
+    content_disposition, content_type, download_filename, select, zip_timestamp, Selection,
+    Validators,
+};
+use convertx_api::{build_router, AppState, Job};
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{create_test_state, generate_token};
+
+/// Create a completed job whose output `filename` holds `content`.
+async fn completed_job(
+    state: &AppState,
+    dir: &TempDir,
+    filename: &str,
+    output_format: &str,
+    content: &[u8],
+) -> Job {
+    let job = Job::new(
+        "user-1".to_string(),
+        "input.bin".to_string(),
+        "bin".to_string(),
+        output_format.to_string(),
+        "tool".to_string(),
+    );
+    let job = state.job_store.create_job(job).await.unwrap();
+
+    let output_dir = dir.path().join("output").join(&job.job_id);
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let output_file = output_dir.join(filename);
+    std::fs::write(&output_file, content).unwrap();
+
+    state
+        .job_store
+        .complete_job(&job.job_id, output_file.to_string_lossy().to_string())
+        .await
+        .unwrap()
+}
+
+async fn download(server: &TestServer, job_id: &str, query: &str) -> axum_test::TestResponse {
+    download_with(server, job_id, query, &[]).await
+}
+
+async fn download_with(
+    server: &TestServer,
+    job_id: &str,
+    query: &str,
+    headers: &[(header::HeaderName, &str)],
+) -> axum_test::TestResponse {
+    let mut request = server
+        .get(&format!("/api/v1/jobs/{}/download{}", job_id, query))
+        .add_header(
+            header::AUTHORIZATION,
+            format!("Bearer {}", generate_token("user-1")),
+        );
+    for (name, value) in headers {
+        request = request.add_header(name.clone(), value.to_string());
+    }
+    request.await
+}
+
+fn request_headers(headers: &[(header::HeaderName, &str)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+    }
+    map
+}
+
+mod header_tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type("mp4"), "video/mp4");
+        assert_eq!(content_type("pdf"), "application/pdf");
+        assert_eq!(content_type("PNG"), "image/png");
+        assert_eq!(content_type("html"), "text/html; charset=utf-8");
+        assert_eq!(content_type("unknownfmt"), "application/octet-stream");
+        assert_eq!(content_type("pdf-zh"), "application/pdf");
+        assert_eq!(content_type("md-t"), "text/markdown; charset=utf-8");
+        assert_eq!(content_type("png-300"), "application/x-tar");
+        assert_eq!(content_type("all-150"), "application/x-tar");
+        assert_eq!(content_type("pdfa1b-i-300"), "application/pdf");
+        assert_eq!(content_type("pdfa2b-o-600"), "application/pdf");
+        assert_eq!(content_type("pdf-300-p"), "application/pdf");
+    }
+
+    #[test]
+    fn test_download_filename() {
+        let name = |path: &str, format: &str| download_filename(std::path::Path::new(path), format);
+        assert_eq!(name("out/doc.pdf-zh", "pdf-zh").as_deref(), Some("doc.pdf"));
+        assert_eq!(
+            name("out/scan.png-300", "png-300").as_deref(),
+            Some("scan.tar")
+        );
+        assert_eq!(
+            name("out/pack.pdfa1b-i-300", "pdfa1b-i-300").as_deref(),
+            Some("pack.pdf")
+        );
+        assert_eq!(
+            name("out/pack.all-600", "all-600").as_deref(),
+            Some("pack.tar")
+        );
+        assert_eq!(name("out/doc.pdf", "pdf").as_deref(), Some("doc.pdf"));
+        assert_eq!(
+            name("out/result.bin", "pdf-zh").as_deref(),
+            Some("result.bin")
+        );
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            content_disposition("report.pdf"),
+            "attachment; filename=\"report.pdf\""
+        );
+        assert_eq!(
+            content_disposition("報告 2024.pdf"),
+            "attachment; filename=\"__ 2024.pdf\"; filename*=UTF-8''%E5%A0%B1%E5%91%8A%202024.pdf"
+        );
+        assert_eq!(
+            content_disposition("a\"b.txt"),
+            "attachment; filename=\"a_b.txt\"; filename*=UTF-8''a%22b.txt"
+        );
+    }
+}
+
+mod rest_download_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_raw_download_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let content = vec![7u8; 64 * 1024];
+        let job = completed_job(&state, &dir, "影片.mp4", "mp4", &content).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = download(&server, &job.job_id, "").await;
+        response.assert_status_ok();
+        assert_eq!(response.header(header::CONTENT_TYPE), "video/mp4");
+        assert_eq!(
+            response.header(header::CONTENT_LENGTH),
+            content.len().to_string().as_str()
+        );
+        let disposition = response.header(header::CONTENT_DISPOSITION);
+        assert!(disposition
+            .to_str()
+            .unwrap()
+            .ends_with("filename*=UTF-8''%E5%BD%B1%E7%89%87.mp4"));
+        assert_eq!(response.as_bytes().as_ref(), content.as_slice());
+
+        // 預設不產生 ZIP
+        let zip_path = dir
+            .path()
+            .join("output")
+            .join(&job.job_id)
+            .join(format!("{}.zip", job.job_id));
+        assert!(!zip_path.exists());
+
+        let response = download(&server, &job.job_id, "?format=raw").await;
+        response.assert_status_ok();
+        assert_eq!(response.as_bytes().as_ref(), content.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_suffixed_format_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = completed_job(&state, &dir, "paper.pdf-zh", "pdf-zh", b"%PDF-1.7").await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = download(&server, &job.job_id, "").await;
+        response.assert_status_ok();
+        assert_eq!(response.header(header::CONTENT_TYPE), "application/pdf");
+        assert_eq!(
+            response.header(header::CONTENT_DISPOSITION),
+            "attachment; filename=\"paper.pdf\""
+        );
+
+        let response = download(&server, &job.job_id, "?format=zip").await;
+        response.assert_status_ok();
+        let bytes = response.as_bytes().to_vec();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "paper.pdf");
+    }
+
+    #[tokio::test]
+    async fn test_zip_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = completed_job(&state, &dir, "筆記.html", "html", b"<h1>notes</h1>").await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = download(&server, &job.job_id, "?format=zip").await;
+        response.assert_status_ok();
+        assert_eq!(response.header(header::CONTENT_TYPE), "application/zip");
+        assert_eq!(
+            response.header(header::CONTENT_DISPOSITION),
+            format!("attachment; filename=\"{}.zip\"", job.job_id).as_str()
+        );
+
+        let bytes = response.as_bytes().to_vec();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.len(), 1);
+        let mut entry = archive.by_index(0).unwrap();
+        assert_eq!(entry.name(), "筆記.html");
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "<h1>notes</h1>");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_format_and_missing_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = completed_job(&state, &dir, "out.pdf", "pdf", b"%PDF-1.7").await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = download(&server, &job.job_id, "?format=tar").await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        assert_eq!(body["code"], "INVALID_INPUT");
+
+        std::fs::remove_dir_all(dir.path().join("output").join(&job.job_id)).unwrap();
+        let response = download(&server, &job.job_id, "").await;
+        response.assert_status(StatusCode::NOT_FOUND);
+        let body: Value = response.json();
+        assert_eq!(body["code"], "FILE_NOT_FOUND");
+    }
+}
+
+mod range_tests {
+    use super::*;
+
+    const LEN: u64 = 1000;
+
+    fn validators() -> Validators {
+        Validators::new("abc", Some(1_700_000_000))
+    }
+
+    fn selected(headers: &[(header::HeaderName, &str)]) -> Selection {
+        select(&request_headers(headers), &validators(), LEN).unwrap()
+    }
+
+    #[test]
+    fn test_byte_ranges() {
+        assert_eq!(selected(&[]), Selection::Full);
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=0-99")]),
+            Selection::Partial { start: 0, end: 99 }
+        );
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=900-")]),
+            Selection::Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=-100")]),
+            Selection::Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=990-5000")]),
+            Selection::Partial {
+                start: 990,
+                end: 999
+            }
+        );
+
+        // 無法解析或多個範圍時回傳完整檔案
+        assert_eq!(selected(&[(header::RANGE, "bytes=5-1")]), Selection::Full);
+        assert_eq!(selected(&[(header::RANGE, "items=0-1")]), Selection::Full);
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=0-1,5-9")]),
+            Selection::Full
+        );
+
+        let headers = request_headers(&[(header::RANGE, "bytes=1000-")]);
+        assert!(select(&headers, &validators(), LEN).is_err());
+        let headers = request_headers(&[(header::RANGE, "bytes=-0")]);
+        assert!(select(&headers, &validators(), LEN).is_err());
+    }
+
+    #[test]
+    fn test_conditional_headers() {
+        let modified = "Tue, 14 Nov 2023 22:13:20 GMT";
+        let later = "Wed, 15 Nov 2023 00:00:00 GMT";
+        let earlier = "Mon, 13 Nov 2023 00:00:00 GMT";
+
+        assert_eq!(
+            selected(&[(header::IF_NONE_MATCH, "\"abc\"")]),
+            Selection::NotModified
+        );
+        assert_eq!(
+            selected(&[(header::IF_NONE_MATCH, "W/\"abc\"")]),
+            Selection::NotModified
+        );
+        assert_eq!(
+            selected(&[(header::IF_NONE_MATCH, "\"x\", \"abc\"")]),
+            Selection::NotModified
+        );
+        assert_eq!(
+            selected(&[(header::IF_NONE_MATCH, "*")]),
+            Selection::NotModified
+        );
+        assert_eq!(
+            selected(&[(header::IF_NONE_MATCH, "\"other\"")]),
+            Selection::Full
+        );
+
+        assert_eq!(
+            selected(&[(header::IF_MODIFIED_SINCE, modified)]),
+            Selection::NotModified
+        );
+        assert_eq!(
+            selected(&[(header::IF_MODIFIED_SINCE, later)]),
+            Selection::NotModified
+        );
+        assert_eq!(
+            selected(&[(header::IF_MODIFIED_SINCE, earlier)]),
+            Selection::Full
+        );
+        // If-None-Match 優先於 If-Modified-Since
+        assert_eq!(
+            selected(&[
+                (header::IF_NONE_MATCH, "\"other\""),
+                (header::IF_MODIFIED_SINCE, later)
+            ]),
+            Selection::Full
+        );
+
+        let partial = Selection::Partial { start: 0, end: 9 };
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=0-9"), (header::IF_RANGE, "\"abc\"")]),
+            partial
+        );
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=0-9"), (header::IF_RANGE, modified)]),
+            partial
+        );
+        assert_eq!(
+            selected(&[
+                (header::RANGE, "bytes=0-9"),
+                (header::IF_RANGE, "\"other\"")
+            ]),
+            Selection::Full
+        );
+        assert_eq!(
+            selected(&[
+                (header::RANGE, "bytes=0-9"),
+                (header::IF_RANGE, "W/\"abc\"")
+            ]),
+            Selection::Full
+        );
+        assert_eq!(
+            selected(&[(header::RANGE, "bytes=0-9"), (header::IF_RANGE, later)]),
+            Selection::Full
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partial_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let content: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let job = completed_job(&state, &dir, "clip.mp4", "mp4", &content).await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let response = download(&server, &job.job_id, "").await;
+        response.assert_status_ok();
+        assert_eq!(response.header(header::ACCEPT_RANGES), "bytes");
+        let etag = response.header(header::ETAG).to_str().unwrap().to_string();
+        let last_modified = response
+            .header(header::LAST_MODIFIED)
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // ETag 來自輸出檔案的 SHA-256，並記錄在任務上
+        let checksum = state
+            .job_store
+            .get_job(&job.job_id)
+            .await
+            .unwrap()
+            .output_checksum
+            .unwrap();
+        assert_eq!(etag, format!("\"{}\"", checksum));
+        assert_eq!(
+            last_modified,
+            httpdate::fmt_http_date(
+                std::time::UNIX_EPOCH
+                    + std::time::Duration::from_secs(job.completed_at.unwrap() as u64)
+            )
+        );
+
+        let response = download_with(
+            &server,
+            &job.job_id,
+            "",
+            &[
+                (header::RANGE, "bytes=1000-1999"),
+                (header::IF_RANGE, etag.as_str()),
+            ],
+        )
+        .await;
+        response.assert_status(StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.header(header::CONTENT_RANGE),
+            "bytes 1000-1999/100000"
+        );
+        assert_eq!(response.header(header::CONTENT_LENGTH), "1000");
+        assert_eq!(response.header(header::CONTENT_TYPE), "video/mp4");
+        assert_eq!(response.as_bytes().as_ref(), &content[1000..2000]);
+
+        let response =
+            download_with(&server, &job.job_id, "", &[(header::RANGE, "bytes=-10")]).await;
+        response.assert_status(StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.as_bytes().as_ref(), &content[content.len() - 10..]);
+
+        let response = download_with(
+            &server,
+            &job.job_id,
+            "",
+            &[(header::RANGE, "bytes=100000-")],
+        )
+        .await;
+        response.assert_status(StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.header(header::CONTENT_RANGE), "bytes */100000");
+        let body: Value = response.json();
+        assert_eq!(body["code"], "RANGE_NOT_SATISFIABLE");
+
+        let response = download_with(
+            &server,
+            &job.job_id,
+            "",
+            &[(header::IF_NONE_MATCH, etag.as_str())],
+        )
+        .await;
+        response.assert_status(StatusCode::NOT_MODIFIED);
+        assert_eq!(response.header(header::ETAG), etag.as_str());
+        assert!(response.as_bytes().is_empty());
+
+        let response = download_with(
+            &server,
+            &job.job_id,
+            "",
+            &[(header::IF_MODIFIED_SINCE, last_modified.as_str())],
+        )
+        .await;
+        response.assert_status(StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn test_zip_range_is_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = completed_job(&state, &dir, "report.pdf", "pdf", &[b'x'; 5000]).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let full = download(&server, &job.job_id, "?format=zip").await;
+        full.assert_status_ok();
+        let etag = full.header(header::ETAG).to_str().unwrap().to_string();
+        let full = full.as_bytes().to_vec();
+
+        let response = download_with(
+            &server,
+            &job.job_id,
+            "?format=zip",
+            &[
+                (header::RANGE, "bytes=10-"),
+                (header::IF_RANGE, etag.as_str()),
+            ],
+        )
+        .await;
+        response.assert_status(StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.as_bytes().as_ref(), &full[10..]);
+
+        // 原始檔案與 ZIP 的 ETag 不同
+        let raw = download(&server, &job.job_id, "").await;
+        assert_ne!(raw.header(header::ETAG).to_str().unwrap(), etag);
+    }
+
+    #[tokio::test]
+    async fn test_regenerated_zip_matches_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = completed_job(&state, &dir, "report.pdf", "pdf", &[b'x'; 5000]).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let first = download(&server, &job.job_id, "?format=zip").await;
+        let etag = first.header(header::ETAG).to_str().unwrap().to_string();
+        let first = first.as_bytes().to_vec();
+
+        // ZIP 內的修改時間為任務完成時間，而非產生 ZIP 的時間
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(first.clone())).unwrap();
+        assert_eq!(
+            archive.by_index(0).unwrap().last_modified(),
+            Some(zip_timestamp(job.completed_at))
+        );
+
+        // 快取的 ZIP 被清除後重新產生，內容與 ETag 都不變
+        let zip_path = dir
+            .path()
+            .join("output")
+            .join(&job.job_id)
+            .join(format!("{}.zip", job.job_id));
+        std::fs::remove_file(&zip_path).unwrap();
+        let second = download(&server, &job.job_id, "?format=zip").await;
+        assert_eq!(second.header(header::ETAG).to_str().unwrap(), etag);
+        assert_eq!(second.as_bytes().to_vec(), first);
+    }
+}
@@ -0,0 +1,149 @@
+//! Engine conversion matrix tests
+
+use axum::http::{header, StatusCode};
+use axum_test::TestServer;
+use convertx_api::engine::EngineCapability;
+use convertx_api::{build_router, Engine, EngineRegistry};
+use serde_json::Value;
+
+mod common;
+
+use common::{create_test_state, generate_token};
+
+fn engine(capabilities: Vec<EngineCapability>) -> Engine {
+    Engine {
+        engine_name: "Test".to_string(),
+        capabilities,
+        ..common::engine("test", &["a", "b"], &["x", "y"])
+    }
+}
+
+mod matrix_tests {
+    use super::*;
+
+    #[test]
+    fn test_without_capabilities_uses_cross_product() {
+        let engine = engine(Vec::new());
+        assert!(engine.supports_conversion("a", "y"));
+        assert!(engine.supports_conversion("B", "X"));
+
+        let matrix = engine.conversion_matrix();
+        assert_eq!(matrix["a"], vec!["x", "y"]);
+        assert_eq!(matrix["b"], vec!["x", "y"]);
+    }
+
+    #[test]
+    fn test_capabilities_restrict_pairings() {
+        let engine = engine(vec![EngineCapability {
+            input_format: "a".to_string(),
+            output_formats: vec!["x".to_string()],
+        }]);
+
+        assert!(engine.supports_conversion("a", "x"));
+        assert!(!engine.supports_conversion("a", "y"));
+        assert!(!engine.supports_conversion("b", "x"));
+        assert_eq!(engine.conversion_matrix().len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_engine_supports_nothing() {
+        let mut engine = engine(Vec::new());
+        engine.enabled = false;
+        assert!(!engine.supports_conversion("a", "x"));
+    }
+
+    #[tokio::test]
+    async fn test_builtin_matrices() {
+        let registry = EngineRegistry::new();
+
+        let ffmpeg = registry.get_engine("ffmpeg").await.unwrap();
+        assert!(ffmpeg.supports_conversion("mp4", "webm"));
+        assert!(ffmpeg.supports_conversion("mp4", "mp3"));
+        assert!(ffmpeg.supports_conversion("flac", "mp3"));
+        assert!(!ffmpeg.supports_conversion("mp3", "mp4"));
+
+        let libjxl = registry.get_engine("libjxl").await.unwrap();
+        assert!(libjxl.supports_conversion("jxl", "png"));
+        assert!(libjxl.supports_conversion("png", "jxl"));
+        assert!(!libjxl.supports_conversion("png", "jpeg"));
+
+        let deark = registry.get_engine("deark").await.unwrap();
+        assert!(deark.supports_conversion("pcx", "png"));
+        assert!(!deark.supports_conversion("zip", "png"));
+        assert!(!deark.conversion_matrix().contains_key("zip"));
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_are_within_flat_lists() {
+        let registry = EngineRegistry::new();
+        for engine in registry.list_engines().await {
+            for (input, outputs) in engine.conversion_matrix() {
+                assert!(
+                    engine.input_formats.contains(&input),
+                    "{}: {} missing from input_formats",
+                    engine.engine_id,
+                    input
+                );
+                for output in outputs {
+                    assert!(
+                        engine.output_formats.contains(&output),
+                        "{}: {} missing from output_formats",
+                        engine.engine_id,
+                        output
+                    );
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_selection_respects_matrix() {
+        let registry = EngineRegistry::new();
+        let engine = registry.find_engine_for_conversion("mp3", "mp4", 0).await;
+        assert!(engine.is_none());
+    }
+}
+
+mod rest_matrix_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_engine_conversions() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        let response = server
+            .get("/api/v1/engines/libjxl/conversions")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["data"]["engine_id"], "libjxl");
+        let conversions = body["data"]["conversions"].as_object().unwrap();
+        assert_eq!(conversions["png"], serde_json::json!(["jxl"]));
+        assert!(conversions["jxl"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("png")));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_engine_conversions() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        let response = server
+            .get("/api/v1/engines/nope/conversions")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
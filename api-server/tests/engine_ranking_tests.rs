@@ -0,0 +1,205 @@
+//! Engine auto-selection ranking tests
+
+use axum::http::header;
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::catalog::{parse_catalog, CatalogError, CatalogFormat};
+use convertx_api::{build_router, Engine, EngineRegistry};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_test_state, generate_token};
+
+/// An engine converting `txt` to `out` and `md` with the given priority.
+fn engine(engine_id: &str, priority: i32) -> Engine {
+    Engine {
+        priority,
+        ..common::engine(engine_id, &["txt"], &["out", "md"])
+    }
+}
+
+async fn ranked_ids(registry: &EngineRegistry, input: &str, output: &str) -> Vec<String> {
+    registry
+        .rank_engines_for_conversion(input, output)
+        .await
+        .into_iter()
+        .map(|e| e.engine_id)
+        .collect()
+}
+
+mod ranking_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_priority_then_engine_id() {
+        let registry = EngineRegistry::with_engines(vec![
+            engine("zeta", 5),
+            engine("beta", 0),
+            engine("alpha", 0),
+            engine("gamma", 5),
+        ]);
+
+        assert_eq!(
+            ranked_ids(&registry, "txt", "out").await,
+            vec!["gamma", "zeta", "alpha", "beta"]
+        );
+        let chosen = registry
+            .find_engine_for_conversion("txt", "out", 0)
+            .await
+            .unwrap();
+        assert_eq!(chosen.engine_id, "gamma");
+    }
+
+    #[tokio::test]
+    async fn test_conversion_priority_overrides_default() {
+        let mut preferred = engine("low", 0);
+        preferred
+            .conversion_priorities
+            .insert("txt->md".to_string(), 100);
+        let registry = EngineRegistry::with_engines(vec![engine("high", 50), preferred]);
+
+        assert_eq!(
+            ranked_ids(&registry, "txt", "out").await,
+            vec!["high", "low"]
+        );
+        assert_eq!(
+            ranked_ids(&registry, "TXT", "MD").await,
+            vec!["low", "high"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_builtin_image_ranking_is_stable() {
+        let registry = EngineRegistry::new();
+
+        let first = ranked_ids(&registry, "png", "jpg").await;
+        assert_eq!(&first[..3], ["vips", "imagemagick", "graphicsmagick"]);
+        for _ in 0..20 {
+            assert_eq!(ranked_ids(&registry, "png", "jpg").await, first);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_size_limit_falls_through_ranking() {
+        let mut top = engine("top", 10);
+        top.max_file_size_mb = 1;
+        let registry = EngineRegistry::with_engines(vec![top, engine("next", 0)]);
+
+        let chosen = registry
+            .find_engine_for_conversion("txt", "out", 2 * 1024 * 1024)
+            .await
+            .unwrap();
+        assert_eq!(chosen.engine_id, "next");
+    }
+
+    #[test]
+    fn test_catalog_conversion_priorities() {
+        let toml = r#"
+[[engines]]
+engine_id = "tool"
+engine_name = "Tool"
+input_formats = ["png"]
+output_formats = ["jpg"]
+max_file_size_mb = 10
+priority = 5
+conversion_priorities = { ".PNG->JPG" = 90 }
+"#;
+        let engines = parse_catalog(toml, CatalogFormat::Toml).unwrap();
+        assert_eq!(engines[0].priority, 5);
+        assert_eq!(engines[0].priority_for("png", "jpg"), 90);
+
+        let invalid = toml.replace(".PNG->JPG", "png");
+        match parse_catalog(&invalid, CatalogFormat::Toml) {
+            Err(CatalogError::Invalid(errors)) => {
+                assert!(errors[0].contains("conversion_priorities"));
+            }
+            other => panic!(
+                "expected validation error, got {:?}",
+                other.map(|e| e.len())
+            ),
+        }
+    }
+}
+
+mod rest_ranking_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_job_exposes_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![
+                engine("second", 1),
+                engine("first", 2),
+                engine("third", 1),
+            ])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+        let token = format!("Bearer {}", generate_token("user-1"));
+
+        let form = MultipartForm::new()
+            .add_part(
+                "file",
+                Part::bytes(b"hello".to_vec()).file_name("notes.txt"),
+            )
+            .add_text("params", json!({ "output_format": "out" }).to_string());
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(header::AUTHORIZATION, token.clone())
+            .multipart(form)
+            .await;
+        response.assert_status_ok();
+        let body: Value = response.json();
+        let expected = json!(["first", "second", "third"]);
+        assert_eq!(body["data"]["engine_id"], "first");
+        assert_eq!(body["data"]["engine_candidates"], expected);
+
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let response = server
+            .get(&format!("/api/v1/jobs/{}", job_id))
+            .add_header(header::AUTHORIZATION, token)
+            .await;
+        let body: Value = response.json();
+        assert_eq!(body["data"]["engine_id"], "first");
+        assert_eq!(body["data"]["engine_candidates"], expected);
+    }
+
+    #[tokio::test]
+    async fn test_explicit_engine_keeps_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("first", 2), engine("second", 1)])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let form = MultipartForm::new()
+            .add_part(
+                "file",
+                Part::bytes(b"hello".to_vec()).file_name("notes.txt"),
+            )
+            .add_text(
+                "params",
+                json!({ "output_format": "out", "engine_id": "second" }).to_string(),
+            );
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .multipart(form)
+            .await;
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["data"]["engine_id"], "second");
+        assert_eq!(
+            body["data"]["engine_candidates"],
+            json!(["first", "second"])
+        );
+    }
+}
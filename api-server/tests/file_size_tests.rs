@@ -0,0 +1,165 @@
+//! Per-engine file size limit tests
+
+use axum::http::{header, StatusCode};
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::{build_router, Engine, EngineRegistry};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_test_state, generate_token};
+
+const MB: usize = 1024 * 1024;
+
+/// An engine converting `txt` to `out` with the given limit.
+fn engine(engine_id: &str, max_file_size_mb: u64) -> Engine {
+    Engine {
+        max_file_size_mb,
+        ..common::engine(engine_id, &["txt"], &["out"])
+    }
+}
+
+fn two_engines() -> Vec<Engine> {
+    vec![engine("small", 1), engine("big", 2)]
+}
+
+async fn upload(server: &TestServer, size: usize, engine_id: Option<&str>) -> (StatusCode, Value) {
+    let mut params = json!({ "output_format": "out" });
+    if let Some(engine_id) = engine_id {
+        params["engine_id"] = json!(engine_id);
+    }
+    let form = MultipartForm::new()
+        .add_part("file", Part::bytes(vec![b'a'; size]).file_name("notes.txt"))
+        .add_text("params", params.to_string());
+
+    let response = server
+        .post("/api/v1/convert")
+        .add_header(
+            header::AUTHORIZATION,
+            format!("Bearer {}", generate_token("user-1")),
+        )
+        .multipart(form)
+        .await;
+    (response.status_code(), response.json())
+}
+
+mod registry_tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_boundary() {
+        let engine = engine("small", 1);
+        assert_eq!(engine.max_file_size_bytes(), MB as u64);
+        assert!(engine.accepts_file_size(MB as u64));
+        assert!(!engine.accepts_file_size(MB as u64 + 1));
+    }
+
+    #[tokio::test]
+    async fn test_auto_selection_skips_engines_over_limit() {
+        let registry = EngineRegistry::with_engines(two_engines());
+
+        let engine = registry
+            .find_engine_for_conversion("txt", "out", (MB + MB / 2) as u64)
+            .await
+            .unwrap();
+        assert_eq!(engine.engine_id, "big");
+
+        assert!(registry
+            .find_engine_for_conversion("txt", "out", 3 * MB as u64)
+            .await
+            .is_none());
+        assert_eq!(
+            registry.max_file_size_for_conversion("txt", "out").await,
+            Some(2 * MB as u64)
+        );
+        assert_eq!(
+            registry.max_file_size_for_conversion("txt", "pdf").await,
+            None
+        );
+    }
+}
+
+mod rest_upload_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_explicit_engine_limit_enforced() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state.engine_registry.replace_engines(two_engines()).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let (status, body) = upload(&server, MB + MB / 2, Some("small")).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["code"], "FILE_TOO_LARGE");
+        assert!(body["message"].as_str().unwrap().contains(&MB.to_string()));
+        // 上傳目錄中不留下暫存檔
+        let uploads = std::fs::read_dir(dir.path().join("uploads")).unwrap();
+        assert_eq!(uploads.count(), 0);
+
+        let (status, _) = upload(&server, MB + MB / 2, Some("big")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_auto_selection_uses_engine_with_room() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state.engine_registry.replace_engines(two_engines()).await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let (status, body) = upload(&server, MB + MB / 2, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let job = state.job_store.get_job(job_id).await.unwrap();
+        assert_eq!(job.engine_id, "big");
+
+        let (status, body) = upload(&server, 3 * MB, None).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .contains(&(2 * MB).to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_global_limit_enforced() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = create_test_state(&dir);
+        state.config.max_file_size = MB as u64;
+        state.engine_registry.replace_engines(two_engines()).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        // 超過請求大小上限與僅超過檔案大小上限，都回報為檔案過大
+        for size in [MB + MB / 2, 3 * MB] {
+            let (status, body) = upload(&server, size, Some("big")).await;
+            assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+            assert_eq!(body["code"], "FILE_TOO_LARGE");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_conversion_still_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state.engine_registry.replace_engines(two_engines()).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(vec![b'a'; 10]).file_name("notes.txt"))
+            .add_text("params", json!({ "output_format": "pdf" }).to_string());
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .multipart(form)
+            .await;
+
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        assert_eq!(body["code"], "UNSUPPORTED_CONVERSION");
+    }
+}
@@ -0,0 +1,195 @@
+//! Retention sweeper tests
+
+use std::time::Duration;
+
+use axum_test::TestServer;
+use convertx_api::janitor::{self, RetentionPolicy, RetentionTotals};
+use convertx_api::{build_router, AppConfig, AppState, Job, JobStatus};
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::create_test_state;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn policy(completed_ttl: Duration, failed_ttl: Duration) -> RetentionPolicy {
+    RetentionPolicy {
+        completed_ttl,
+        failed_ttl,
+        interval: Duration::ZERO,
+    }
+}
+
+/// Create a job with a 10-byte upload and a 20-byte output, then move it to `status`.
+async fn create_job(state: &AppState, dir: &TempDir, status: JobStatus) -> Job {
+    let job = Job::new(
+        "user-1".to_string(),
+        "data.json".to_string(),
+        "json".to_string(),
+        "yaml".to_string(),
+        "dasel".to_string(),
+    );
+    let job = state.job_store.create_job(job).await.unwrap();
+
+    let upload_dir = dir.path().join("uploads").join(&job.job_id);
+    std::fs::create_dir_all(&upload_dir).unwrap();
+    std::fs::write(upload_dir.join("data.json"), [0u8; 10]).unwrap();
+
+    let output_dir = dir.path().join("output").join(&job.job_id);
+    std::fs::create_dir_all(output_dir.join("nested")).unwrap();
+    std::fs::write(output_dir.join("nested").join("data.yaml"), [0u8; 20]).unwrap();
+
+    let store = &state.job_store;
+    match status {
+        JobStatus::Completed => {
+            store
+                .complete_job(
+                    &job.job_id,
+                    output_dir.join("data.yaml").to_string_lossy().to_string(),
+                )
+                .await;
+        }
+        JobStatus::Failed => {
+            store.fail_job(&job.job_id, "boom".to_string()).await;
+        }
+        JobStatus::Cancelled => {
+            store.cancel_job(&job.job_id).await;
+        }
+        JobStatus::Processing => {
+            store
+                .update_status(&job.job_id, JobStatus::Processing)
+                .await;
+        }
+        JobStatus::Pending => {}
+    }
+    job
+}
+
+fn files_exist(dir: &TempDir, job_id: &str) -> bool {
+    dir.path().join("uploads").join(job_id).exists()
+        || dir.path().join("output").join(job_id).exists()
+}
+
+mod janitor_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expired_completed_job_removed_with_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = create_job(&state, &dir, JobStatus::Completed).await;
+
+        let report = janitor::sweep(&state, &policy(Duration::ZERO, HOUR)).await;
+
+        assert_eq!(report.removed, vec![job.job_id.clone()]);
+        assert_eq!(report.reclaimed_bytes, 30);
+        assert!(state.job_store.get_job(&job.job_id).await.is_none());
+        assert!(!files_exist(&dir, &job.job_id));
+    }
+
+    #[tokio::test]
+    async fn test_reclaimed_totals_reported_by_health_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        create_job(&state, &dir, JobStatus::Completed).await;
+        janitor::sweep(&state, &policy(Duration::ZERO, HOUR)).await;
+        create_job(&state, &dir, JobStatus::Failed).await;
+        janitor::sweep(&state, &policy(Duration::ZERO, Duration::ZERO)).await;
+
+        assert_eq!(
+            state.retention_stats.totals(),
+            RetentionTotals {
+                removed_jobs: 2,
+                expired_uploads: 0,
+                reclaimed_bytes: 60,
+            }
+        );
+
+        let server = TestServer::new(build_router(state)).unwrap();
+        let body: Value = server.get("/health").await.json();
+        assert_eq!(body["data"]["retention"]["removed_jobs"], 2);
+        assert_eq!(body["data"]["retention"]["reclaimed_bytes"], 60);
+    }
+
+    #[tokio::test]
+    async fn test_separate_ttls_for_failed_and_completed() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let completed = create_job(&state, &dir, JobStatus::Completed).await;
+        let failed = create_job(&state, &dir, JobStatus::Failed).await;
+        let cancelled = create_job(&state, &dir, JobStatus::Cancelled).await;
+
+        let report = janitor::sweep(&state, &policy(HOUR, Duration::ZERO)).await;
+
+        assert_eq!(report.removed.len(), 2);
+        assert!(report.removed.contains(&failed.job_id));
+        assert!(report.removed.contains(&cancelled.job_id));
+        assert!(state.job_store.get_job(&completed.job_id).await.is_some());
+        assert!(files_exist(&dir, &completed.job_id));
+        assert!(!files_exist(&dir, &failed.job_id));
+    }
+
+    #[tokio::test]
+    async fn test_active_jobs_never_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let pending = create_job(&state, &dir, JobStatus::Pending).await;
+        let processing = create_job(&state, &dir, JobStatus::Processing).await;
+
+        let report = janitor::sweep(&state, &policy(Duration::ZERO, Duration::ZERO)).await;
+
+        assert!(report.removed.is_empty());
+        assert_eq!(report.reclaimed_bytes, 0);
+        assert!(state.job_store.get_job(&pending.job_id).await.is_some());
+        assert!(files_exist(&dir, &processing.job_id));
+    }
+
+    #[tokio::test]
+    async fn test_recent_jobs_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = create_job(&state, &dir, JobStatus::Completed).await;
+
+        let report = janitor::sweep(&state, &policy(HOUR, HOUR)).await;
+
+        assert!(report.removed.is_empty());
+        assert!(files_exist(&dir, &job.job_id));
+    }
+
+    #[tokio::test]
+    async fn test_missing_files_still_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let job = create_job(&state, &dir, JobStatus::Failed).await;
+        std::fs::remove_dir_all(dir.path().join("uploads").join(&job.job_id)).unwrap();
+        std::fs::remove_dir_all(dir.path().join("output").join(&job.job_id)).unwrap();
+
+        let report = janitor::sweep(&state, &policy(HOUR, Duration::ZERO)).await;
+
+        assert_eq!(report.removed, vec![job.job_id]);
+        assert_eq!(report.reclaimed_bytes, 0);
+    }
+
+    #[test]
+    fn test_retention_policy_from_config() {
+        let config = AppConfig {
+            completed_job_ttl_hours: 2,
+            failed_job_ttl_hours: 1,
+            cleanup_interval_secs: 0,
+            ..Default::default()
+        };
+        let policy = config.retention_policy();
+        assert_eq!(policy.completed_ttl, 2 * HOUR);
+        assert_eq!(policy.failed_ttl, HOUR);
+        assert!(policy.interval.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_sweeper_not_started() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        assert!(janitor::start(state, policy(HOUR, HOUR)).is_none());
+    }
+}
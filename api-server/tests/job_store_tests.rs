@@ -0,0 +1,173 @@
+//! Job store persistence tests
+
+use convertx_api::{JobPriority, JobStatus, JobStore};
+
+mod common;
+
+use common::sample_job;
+
+mod job_store_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_and_get_job() {
+        let store = JobStore::new();
+        let job = store.create_job(sample_job("user-1")).await.unwrap();
+
+        let fetched = store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(fetched.job_id, job.job_id);
+        assert_eq!(fetched.user_id, "user-1");
+        assert_eq!(fetched.status, JobStatus::Pending);
+        assert_eq!(fetched.progress, 0);
+        assert!(fetched.completed_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_job() {
+        let store = JobStore::new();
+        assert!(store.get_job("missing").await.is_none());
+        assert!(store.update_progress("missing", 50).await.is_none());
+        assert!(store
+            .fail_job("missing", "boom".to_string())
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_status_transitions() {
+        let store = JobStore::new();
+        let job = store.create_job(sample_job("user-1")).await.unwrap();
+
+        let job = store
+            .update_status(&job.job_id, JobStatus::Processing)
+            .await
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Processing);
+
+        let job = store.update_progress(&job.job_id, 150).await.unwrap();
+        assert_eq!(job.progress, 100);
+
+        let job = store
+            .complete_job(&job.job_id, "/tmp/out.pdf".to_string())
+            .await
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.output_file.as_deref(), Some("/tmp/out.pdf"));
+        assert!(job.completed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fail_job() {
+        let store = JobStore::new();
+        let job = store.create_job(sample_job("user-1")).await.unwrap();
+
+        let job = store
+            .fail_job(&job.job_id, "backend error".to_string())
+            .await
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error_message.as_deref(), Some("backend error"));
+        assert!(job.completed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_user_jobs_and_ownership() {
+        let store = JobStore::new();
+        let job = store.create_job(sample_job("user-1")).await.unwrap();
+        store.create_job(sample_job("user-1")).await.unwrap();
+        store.create_job(sample_job("user-2")).await.unwrap();
+
+        assert_eq!(store.get_user_jobs("user-1").await.len(), 2);
+        assert_eq!(store.get_user_jobs("user-2").await.len(), 1);
+        assert!(store.is_job_owner(&job.job_id, "user-1").await);
+        assert!(!store.is_job_owner(&job.job_id, "user-2").await);
+    }
+
+    #[tokio::test]
+    async fn test_priority_persisted() {
+        let store = JobStore::new();
+        let job = sample_job("user-1").with_priority(JobPriority::High);
+        let job = store.create_job(job).await.unwrap();
+
+        let fetched = store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(fetched.priority, JobPriority::High);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_job_id_rejected() {
+        let store = JobStore::new();
+        let job = store.create_job(sample_job("user-1")).await.unwrap();
+        assert!(store.create_job(job).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jobs_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.db");
+        let path = path.to_str().unwrap();
+
+        let job_id = {
+            let store = JobStore::open(path).unwrap();
+            let job = store.create_job(sample_job("user-1")).await.unwrap();
+            store
+                .complete_job(&job.job_id, "/tmp/out.pdf".to_string())
+                .await
+                .unwrap();
+            job.job_id
+        };
+
+        let store = JobStore::open(path).unwrap();
+        let job = store.get_job(&job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.output_file.as_deref(), Some("/tmp/out.pdf"));
+    }
+
+    #[tokio::test]
+    async fn test_migrations_are_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("jobs.db");
+        let path = path.to_str().unwrap();
+
+        let first = JobStore::open(path).unwrap();
+        let version = first.database().schema_version().await.unwrap();
+        assert!(version >= 1);
+        drop(first);
+
+        let second = JobStore::open(path).unwrap();
+        assert_eq!(second.database().schema_version().await.unwrap(), version);
+    }
+
+    /// A finished job that ended at `completed_at` but was touched again at `updated_at`.
+    async fn finished_job(
+        store: &JobStore,
+        status: JobStatus,
+        completed_at: Option<i64>,
+        updated_at: i64,
+    ) -> String {
+        let mut job = sample_job("user-1");
+        job.status = status;
+        job.completed_at = completed_at;
+        job.updated_at = updated_at;
+        store.create_job(job).await.unwrap().job_id
+    }
+
+    #[tokio::test]
+    async fn test_expiry_uses_completion_time() {
+        let store = JobStore::new();
+        let completed = finished_job(&store, JobStatus::Completed, Some(100), 5000).await;
+        let failed = finished_job(&store, JobStatus::Failed, Some(200), 5000).await;
+        let cancelled = finished_job(&store, JobStatus::Cancelled, Some(300), 5000).await;
+        // 沒有結束時間的舊資料以 updated_at 為準
+        let legacy = finished_job(&store, JobStatus::Failed, None, 400).await;
+        finished_job(&store, JobStatus::Completed, Some(2000), 100).await;
+        finished_job(&store, JobStatus::Failed, None, 2000).await;
+
+        let expired: Vec<String> = store
+            .get_expired_jobs(1000, 1000)
+            .await
+            .into_iter()
+            .map(|job| job.job_id)
+            .collect();
+        assert_eq!(expired, vec![completed, failed, cancelled, legacy]);
+    }
+}
@@ -0,0 +1,314 @@
+//! Job listing tests
+
+use axum::http::{header, StatusCode};
+use axum_test::TestServer;
+use convertx_api::job::{JobCursor, JobFilter};
+use convertx_api::models::SortOrder;
+use convertx_api::{build_router, Job, JobStatus, JobStore};
+use serde_json::Value;
+
+mod common;
+
+use common::{create_test_state, generate_token};
+
+fn job_at(user_id: &str, created_at: i64, engine_id: &str, output_format: &str) -> Job {
+    let mut job = Job::new(
+        user_id.to_string(),
+        format!("file-{}.png", created_at),
+        "png".to_string(),
+        output_format.to_string(),
+        engine_id.to_string(),
+    );
+    job.created_at = created_at;
+    job.updated_at = created_at;
+    job
+}
+
+/// Five jobs for user-1 created at t=100..=104, plus one job for user-2.
+async fn seed(store: &JobStore) -> Vec<Job> {
+    let mut jobs = Vec::new();
+    for (i, (engine, format)) in [
+        ("imagemagick", "jpg"),
+        ("vips", "webp"),
+        ("imagemagick", "webp"),
+        ("vips", "jpg"),
+        ("imagemagick", "jpg"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let job = job_at("user-1", 100 + i as i64, engine, format);
+        jobs.push(store.create_job(job).await.unwrap());
+    }
+    store
+        .create_job(job_at("user-2", 100, "vips", "jpg"))
+        .await
+        .unwrap();
+    jobs
+}
+
+fn ids(jobs: &[Job]) -> Vec<i64> {
+    jobs.iter().map(|j| j.created_at).collect()
+}
+
+mod store_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pagination_walks_all_jobs() {
+        let store = JobStore::new();
+        seed(&store).await;
+
+        let first = store
+            .list_user_jobs("user-1", JobFilter::default(), SortOrder::Desc, None, 2)
+            .await;
+        assert_eq!(ids(&first.jobs), vec![104, 103]);
+        assert_eq!(first.total, 5);
+
+        let second = store
+            .list_user_jobs(
+                "user-1",
+                JobFilter::default(),
+                SortOrder::Desc,
+                first.next_cursor,
+                2,
+            )
+            .await;
+        assert_eq!(ids(&second.jobs), vec![102, 101]);
+
+        let third = store
+            .list_user_jobs(
+                "user-1",
+                JobFilter::default(),
+                SortOrder::Desc,
+                second.next_cursor,
+                2,
+            )
+            .await;
+        assert_eq!(ids(&third.jobs), vec![100]);
+        assert!(third.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ascending_order() {
+        let store = JobStore::new();
+        seed(&store).await;
+
+        let page = store
+            .list_user_jobs("user-1", JobFilter::default(), SortOrder::Asc, None, 3)
+            .await;
+        assert_eq!(ids(&page.jobs), vec![100, 101, 102]);
+
+        let page = store
+            .list_user_jobs(
+                "user-1",
+                JobFilter::default(),
+                SortOrder::Asc,
+                page.next_cursor,
+                3,
+            )
+            .await;
+        assert_eq!(ids(&page.jobs), vec![103, 104]);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_with_same_timestamp_not_skipped() {
+        let store = JobStore::new();
+        for _ in 0..5 {
+            store
+                .create_job(job_at("user-1", 100, "vips", "jpg"))
+                .await
+                .unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = store
+                .list_user_jobs("user-1", JobFilter::default(), SortOrder::Desc, cursor, 2)
+                .await;
+            seen.extend(page.jobs.into_iter().map(|j| j.job_id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_filters() {
+        let store = JobStore::new();
+        let jobs = seed(&store).await;
+        store.fail_job(&jobs[1].job_id, "boom".to_string()).await;
+
+        let filter = JobFilter {
+            engine_id: Some("imagemagick".to_string()),
+            output_format: Some("JPG".to_string()),
+            ..Default::default()
+        };
+        let page = store
+            .list_user_jobs("user-1", filter, SortOrder::Asc, None, 10)
+            .await;
+        assert_eq!(ids(&page.jobs), vec![100, 104]);
+        assert_eq!(page.total, 2);
+
+        let filter = JobFilter {
+            status: Some(JobStatus::Failed),
+            ..Default::default()
+        };
+        let page = store
+            .list_user_jobs("user-1", filter, SortOrder::Asc, None, 10)
+            .await;
+        assert_eq!(ids(&page.jobs), vec![101]);
+
+        let filter = JobFilter {
+            created_after: Some(101),
+            created_before: Some(103),
+            ..Default::default()
+        };
+        let page = store
+            .list_user_jobs("user-1", filter, SortOrder::Asc, None, 10)
+            .await;
+        assert_eq!(ids(&page.jobs), vec![101, 102]);
+    }
+
+    #[tokio::test]
+    async fn test_filters_ignore_stored_case() {
+        let store = JobStore::new();
+        store
+            .create_job(job_at("user-1", 100, "ImageMagick", "PDF"))
+            .await
+            .unwrap();
+        store
+            .create_job(job_at("user-1", 101, "vips", "Pdf"))
+            .await
+            .unwrap();
+
+        for format in ["pdf", "PDF"] {
+            let filter = JobFilter {
+                output_format: Some(format.to_string()),
+                ..Default::default()
+            };
+            let page = store
+                .list_user_jobs("user-1", filter, SortOrder::Asc, None, 10)
+                .await;
+            assert_eq!(ids(&page.jobs), vec![100, 101], "{}", format);
+        }
+
+        let filter = JobFilter {
+            engine_id: Some("imagemagick".to_string()),
+            ..Default::default()
+        };
+        let page = store
+            .list_user_jobs("user-1", filter, SortOrder::Asc, None, 10)
+            .await;
+        assert_eq!(ids(&page.jobs), vec![100]);
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = JobCursor {
+            created_at: 1700000000,
+            job_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+        };
+        assert_eq!(cursor.to_string().parse::<JobCursor>(), Ok(cursor));
+        assert!("garbage".parse::<JobCursor>().is_err());
+        assert!("abc:def".parse::<JobCursor>().is_err());
+        assert!("123:".parse::<JobCursor>().is_err());
+    }
+}
+
+mod rest_list_tests {
+    use super::*;
+
+    async fn get_jobs(server: &TestServer, user_id: &str, query: &str) -> (StatusCode, Value) {
+        let response = server
+            .get(&format!("/api/v1/jobs{}", query))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token(user_id)),
+            )
+            .await;
+        (response.status_code(), response.json())
+    }
+
+    #[tokio::test]
+    async fn test_list_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        let (status, body) = get_jobs(&server, "user-1", "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["jobs"].as_array().unwrap().is_empty());
+        assert_eq!(body["data"]["total"], 0);
+        assert!(body["data"]["next_cursor"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_list_only_own_jobs_with_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        seed(&state.job_store).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let (_, body) = get_jobs(&server, "user-1", "?limit=3").await;
+        let data = &body["data"];
+        assert_eq!(data["total"], 5);
+        assert_eq!(data["jobs"].as_array().unwrap().len(), 3);
+        assert_eq!(data["jobs"][0]["created_at"], 104);
+
+        let cursor = data["next_cursor"].as_str().unwrap();
+        let (_, body) = get_jobs(&server, "user-1", &format!("?limit=3&cursor={}", cursor)).await;
+        let created: Vec<i64> = body["data"]["jobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|j| j["created_at"].as_i64().unwrap())
+            .collect();
+        assert_eq!(created, vec![101, 100]);
+        assert!(body["data"]["next_cursor"].is_null());
+
+        let (_, body) = get_jobs(&server, "user-2", "").await;
+        assert_eq!(body["data"]["total"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_with_filters_and_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        seed(&state.job_store).await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let (status, body) = get_jobs(
+            &server,
+            "user-1",
+            "?status=pending&engine_id=vips&order=asc&created_after=101",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let created: Vec<i64> = body["data"]["jobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|j| j["created_at"].as_i64().unwrap())
+            .collect();
+        assert_eq!(created, vec![101, 103]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_query_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(create_test_state(&dir))).unwrap();
+
+        let (status, body) = get_jobs(&server, "user-1", "?status=unknown").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["code"].is_string());
+
+        let (status, _) = get_jobs(&server, "user-1", "?cursor=not-a-cursor").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,238 @@
+//! Conversion option validation tests
+
+use axum::http::{header, StatusCode};
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::params::validate_options;
+use convertx_api::{build_router, EngineRegistry};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_test_state, generate_token, with_graphql};
+
+fn paths(violations: &[convertx_api::params::ParamViolation]) -> Vec<&str> {
+    violations.iter().map(|v| v.path.as_str()).collect()
+}
+
+mod schema_tests {
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "lang": { "type": "string", "enum": ["en", "zh"] },
+                "dpi": { "type": "integer", "minimum": 72, "maximum": 600 },
+                "pages": { "type": "array", "items": { "type": "integer" } },
+                "label": { "type": "string", "maxLength": 3 }
+            },
+            "required": ["lang"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_valid_options() {
+        let options = json!({ "lang": "zh", "dpi": 300, "pages": [1, 2], "label": "abc" });
+        assert!(validate_options(&schema(), Some(&options)).is_empty());
+    }
+
+    #[test]
+    fn test_missing_options_checked_against_required() {
+        let violations = validate_options(&schema(), None);
+        assert_eq!(paths(&violations), vec!["options.lang"]);
+        assert_eq!(violations[0].message, "is required");
+    }
+
+    #[test]
+    fn test_every_violation_reported() {
+        let options = json!({
+            "lang": "fr",
+            "dpi": 1.5,
+            "pages": [1, "two"],
+            "label": "toolong",
+            "extra": true
+        });
+
+        let violations = validate_options(&schema(), Some(&options));
+        let mut reported = paths(&violations);
+        reported.sort();
+        assert_eq!(
+            reported,
+            vec![
+                "options.dpi",
+                "options.extra",
+                "options.label",
+                "options.lang",
+                "options.pages[1]"
+            ]
+        );
+
+        let dpi = violations.iter().find(|v| v.path == "options.dpi").unwrap();
+        assert_eq!(dpi.message, "expected integer, got number");
+        let lang = violations
+            .iter()
+            .find(|v| v.path == "options.lang")
+            .unwrap();
+        assert_eq!(lang.message, r#"must be one of: "en", "zh""#);
+    }
+
+    #[test]
+    fn test_range_and_root_type() {
+        let violations = validate_options(&schema(), Some(&json!({ "lang": "en", "dpi": 10 })));
+        assert_eq!(violations[0].message, "must be >= 72");
+
+        let violations = validate_options(&schema(), Some(&json!("en")));
+        assert_eq!(paths(&violations), vec!["options"]);
+        assert_eq!(violations[0].message, "expected object, got string");
+    }
+
+    #[tokio::test]
+    async fn test_builtin_schemas() {
+        let registry = EngineRegistry::new();
+
+        let babeldoc = registry.get_engine("babeldoc").await.unwrap();
+        let schema = babeldoc.params_schema.unwrap();
+        assert!(validate_options(&schema, Some(&json!({ "target_lang": "ja" }))).is_empty());
+        assert_eq!(
+            paths(&validate_options(
+                &schema,
+                Some(&json!({ "target_lang": "xx" }))
+            )),
+            vec!["options.target_lang"]
+        );
+
+        // 未宣告 additionalProperties 時允許額外的選項
+        let mineru = registry.get_engine("mineru").await.unwrap();
+        let schema = mineru.params_schema.unwrap();
+        assert!(validate_options(&schema, Some(&json!({ "other": 1 }))).is_empty());
+    }
+}
+
+mod rest_params_tests {
+    use super::*;
+
+    async fn convert(server: &TestServer, options: Option<Value>) -> (StatusCode, Value) {
+        let mut params = json!({ "output_format": "pdf-en", "engine_id": "babeldoc" });
+        if let Some(options) = options {
+            params["options"] = options;
+        }
+        let form = MultipartForm::new()
+            .add_part(
+                "file",
+                Part::bytes(b"%PDF-1.4".to_vec()).file_name("paper.pdf"),
+            )
+            .add_text("params", params.to_string());
+
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .multipart(form)
+            .await;
+        (response.status_code(), response.json())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_options_rejected_with_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(with_graphql(create_test_state(&dir)))).unwrap();
+
+        let (status, body) = convert(&server, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_INPUT");
+        assert_eq!(
+            body["details"],
+            json!([{ "path": "options.target_lang", "message": "is required" }])
+        );
+
+        let (status, body) = convert(&server, Some(json!({ "target_lang": 7 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["details"][0]["message"],
+            "expected string, got integer"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_valid_options_accepted() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = with_graphql(create_test_state(&dir));
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let (status, body) = convert(&server, Some(json!({ "target_lang": "zh-TW" }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let job = state.job_store.get_job(job_id).await.unwrap();
+        assert_eq!(job.options, Some(json!({ "target_lang": "zh-TW" })));
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_have_no_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(with_graphql(create_test_state(&dir)))).unwrap();
+
+        let response = server
+            .get("/api/v1/engines/nope")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+        let body: Value = response.json();
+        assert!(body.get("details").is_none());
+    }
+}
+
+mod schema_exposure_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rest_engine_info_includes_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(with_graphql(create_test_state(&dir)))).unwrap();
+
+        let response = server
+            .get("/api/v1/engines/babeldoc")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+        let body: Value = response.json();
+        let schema = &body["data"]["engine"]["params_schema"];
+        assert_eq!(schema["required"], json!(["target_lang"]));
+
+        let response = server
+            .get("/api/v1/engines/ffmpeg")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+        let body: Value = response.json();
+        assert!(body["data"]["engine"]["params_schema"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_graphql_engine_includes_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(build_router(with_graphql(create_test_state(&dir)))).unwrap();
+
+        let response = server
+            .post("/graphql")
+            .json(&json!({ "query": r#"{ engine(id: "babeldoc") { requiresParams paramsSchema } }"# }))
+            .await;
+        let body: Value = response.json();
+        let engine = &body["data"]["engine"];
+        assert_eq!(engine["requiresParams"], true);
+        assert_eq!(
+            engine["paramsSchema"]["properties"]["target_lang"]["type"],
+            "string"
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! Multi-step conversion planning tests
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::Multipart;
+use axum::http::{header, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::planner::{plan_chain, ConversionPlan, ConversionStep, MAX_PLAN_STEPS};
+use convertx_api::{build_router, AppState, Engine, EngineRegistry, JobStatus};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{
+    create_test_state, create_test_state_with_backend, generate_token, serve_backend, start_runner,
+    wait_for_finish,
+};
+
+/// An engine converting `input` to `output`.
+fn engine(engine_id: &str, input: &str, output: &str) -> Engine {
+    common::engine(engine_id, &[input], &[output])
+}
+
+fn step(engine_id: &str, input: &str, output: &str) -> ConversionStep {
+    ConversionStep {
+        engine_id: engine_id.to_string(),
+        input_format: input.to_string(),
+        output_format: output.to_string(),
+    }
+}
+
+fn plan(engines: &[Engine], input: &str, output: &str) -> Option<Vec<ConversionStep>> {
+    let engines: Vec<&Engine> = engines.iter().collect();
+    plan_chain(&engines, input, output, 0).map(|plan| plan.steps)
+}
+
+/// Backend requests as (engine, uploaded filename, target format).
+type Calls = Arc<Mutex<Vec<(String, String, String)>>>;
+
+/// Start a backend stub that records each call and fails for `failing_engine`.
+async fn start_backend(failing_engine: Option<&'static str>) -> (String, Calls) {
+    let calls: Calls = Arc::new(Mutex::new(Vec::new()));
+    let recorder = calls.clone();
+    let app = Router::new().route(
+        "/api/convert",
+        post(move |mut multipart: Multipart| {
+            let recorder = recorder.clone();
+            async move {
+                let (mut engine, mut filename, mut target) =
+                    (String::new(), String::new(), String::new());
+                while let Some(field) = multipart.next_field().await.unwrap() {
+                    match field.name().unwrap_or_default() {
+                        "file" => filename = field.file_name().unwrap_or_default().to_string(),
+                        "engine" => engine = field.text().await.unwrap(),
+                        "targetFormat" => target = field.text().await.unwrap(),
+                        _ => {}
+                    }
+                }
+                let failed = failing_engine == Some(engine.as_str());
+                recorder
+                    .lock()
+                    .unwrap()
+                    .push((engine, filename, target.clone()));
+                if failed {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "boom".to_string())
+                } else {
+                    (StatusCode::OK, format!("converted to {}", target))
+                }
+            }
+        }),
+    );
+    (serve_backend(app).await, calls)
+}
+
+mod chain_tests {
+    use super::*;
+
+    #[test]
+    fn test_two_step_chain() {
+        let engines = vec![engine("first", "a", "b"), engine("second", "b", "c")];
+        assert_eq!(
+            plan(&engines, "a", "c").unwrap(),
+            vec![step("first", "a", "b"), step("second", "b", "c")]
+        );
+        assert_eq!(plan(&engines, "c", "a"), None);
+    }
+
+    #[test]
+    fn test_shortest_chain_wins() {
+        let engines = vec![
+            engine("long1", "a", "x"),
+            engine("long2", "x", "y"),
+            engine("long3", "y", "c"),
+            engine("short1", "a", "b"),
+            engine("short2", "b", "c"),
+        ];
+        let steps = plan(&engines, "a", "c").unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].engine_id, "short1");
+    }
+
+    #[test]
+    fn test_priority_breaks_ties() {
+        let mut preferred = engine("zeta", "a", "b");
+        preferred.priority = 10;
+        let engines = vec![
+            engine("alpha", "a", "b"),
+            preferred,
+            engine("beta", "b", "c"),
+        ];
+
+        let steps = plan(&engines, "a", "c").unwrap();
+        assert_eq!(steps[0].engine_id, "zeta");
+        for _ in 0..10 {
+            assert_eq!(plan(&engines, "a", "c").unwrap(), steps);
+        }
+    }
+
+    #[test]
+    fn test_unusable_engines_skipped() {
+        let mut disabled = engine("disabled", "a", "b");
+        disabled.enabled = false;
+        let mut with_params = engine("params", "a", "b");
+        with_params.requires_params = true;
+        let mut small = engine("small", "a", "b");
+        small.max_file_size_mb = 1;
+
+        let engines = vec![disabled, with_params, small, engine("second", "b", "c")];
+        assert_eq!(plan(&engines, "a", "c").unwrap()[0].engine_id, "small");
+
+        let refs: Vec<&Engine> = engines.iter().collect();
+        assert!(plan_chain(&refs, "a", "c", 2 * 1024 * 1024).is_none());
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let formats = ["a", "b", "c", "d", "e"];
+        let engines: Vec<Engine> = formats
+            .windows(2)
+            .map(|pair| engine(&format!("{}2{}", pair[0], pair[1]), pair[0], pair[1]))
+            .collect();
+
+        assert_eq!(plan(&engines, "a", "d").unwrap().len(), MAX_PLAN_STEPS);
+        assert!(plan(&engines, "a", "e").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_registry_prefers_single_engine() {
+        let registry = EngineRegistry::with_engines(vec![
+            engine("first", "a", "b"),
+            engine("second", "b", "c"),
+            engine("direct", "a", "c"),
+        ]);
+        assert_eq!(
+            registry.plan_conversion("a", "c", 0).await,
+            Some(ConversionPlan::single("direct", "a", "c"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_builtin_chain() {
+        let registry = EngineRegistry::new();
+        let plan = registry.plan_conversion("avci", "pdf", 0).await.unwrap();
+
+        assert!(plan.is_multi_step());
+        assert_eq!(plan.first_engine_id(), "libheif");
+        assert_eq!(plan.steps.last().unwrap().output_format, "pdf");
+    }
+}
+
+mod dry_run_tests {
+    use super::*;
+
+    async fn dry_run(server: &TestServer, body: Value) -> (StatusCode, Value) {
+        let response = server
+            .post("/api/v1/convert/plan")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .json(&body)
+            .await;
+        (response.status_code(), response.json())
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("first", "a", "b"), engine("second", "b", "c")])
+            .await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let (status, body) = dry_run(
+            &server,
+            json!({ "input_format": ".A", "output_format": "c" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["input_format"], "a");
+        assert_eq!(body["data"]["engine_candidates"], json!([]));
+        assert_eq!(
+            body["data"]["steps"],
+            json!([
+                { "engine_id": "first", "input_format": "a", "output_format": "b" },
+                { "engine_id": "second", "input_format": "b", "output_format": "c" }
+            ])
+        );
+
+        // 不建立任務
+        assert!(state.job_store.get_user_jobs("user-1").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_errors_match_convert() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("first", "a", "b"), engine("second", "b", "c")])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let (_, body) = dry_run(
+            &server,
+            json!({ "input_format": "c", "output_format": "a" }),
+        )
+        .await;
+        assert_eq!(body["code"], "UNSUPPORTED_CONVERSION");
+
+        // 指定引擎時不串接
+        let (_, body) = dry_run(
+            &server,
+            json!({ "input_format": "a", "output_format": "c", "engine_id": "first" }),
+        )
+        .await;
+        assert_eq!(body["code"], "UNSUPPORTED_CONVERSION");
+
+        let (status, body) = dry_run(
+            &server,
+            json!({ "input_format": "a", "output_format": "c", "file_size": 20 * 1024 * 1024 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["code"], "FILE_TOO_LARGE");
+
+        let (status, _) = dry_run(&server, json!({ "output_format": "c" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Backend requests as (engine, forwarded options).
+type OptionCalls = Arc<Mutex<Vec<(String, Option<Value>)>>>;
+
+/// Start a backend stub that records the options sent to each engine.
+async fn start_options_backend() -> (String, OptionCalls) {
+    let calls: OptionCalls = Arc::new(Mutex::new(Vec::new()));
+    let recorder = calls.clone();
+    let app = Router::new().route(
+        "/api/convert",
+        post(move |mut multipart: Multipart| {
+            let recorder = recorder.clone();
+            async move {
+                let (mut engine, mut options) = (String::new(), None);
+                while let Some(field) = multipart.next_field().await.unwrap() {
+                    match field.name().unwrap_or_default() {
+                        "engine" => engine = field.text().await.unwrap(),
+                        "options" => {
+                            options =
+                                Some(serde_json::from_str(&field.text().await.unwrap()).unwrap())
+                        }
+                        _ => {}
+                    }
+                }
+                recorder.lock().unwrap().push((engine, options));
+                "converted"
+            }
+        }),
+    );
+    (serve_backend(app).await, calls)
+}
+
+mod multi_step_job_tests {
+    use super::*;
+
+    async fn submit(state: &AppState) -> String {
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(b"data".to_vec()).file_name("doc.a"))
+            .add_text("params", json!({ "output_format": "c" }).to_string());
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .multipart(form)
+            .await;
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["data"]["engine_id"], "first");
+        body["data"]["job_id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_steps_run_in_sequence() {
+        let (backend_url, calls) = start_backend(None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("first", "a", "b"), engine("second", "b", "c")])
+            .await;
+        start_runner(&state);
+
+        let job_id = submit(&state).await;
+        let job = wait_for_finish(&state, &job_id).await;
+
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.progress, 100);
+        assert_eq!(
+            job.steps,
+            vec![step("first", "a", "b"), step("second", "b", "c")]
+        );
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                ("first".to_string(), "doc.a".to_string(), "b".to_string()),
+                ("second".to_string(), "doc.b".to_string(), "c".to_string()),
+            ]
+        );
+
+        let output = job.output_file.unwrap();
+        assert!(output.ends_with("doc.c"));
+        assert_eq!(std::fs::read_to_string(output).unwrap(), "converted to c");
+        assert!(!dir
+            .path()
+            .join("output")
+            .join(&job_id)
+            .join("doc.b")
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_reported() {
+        let (backend_url, calls) = start_backend(Some("second")).await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("first", "a", "b"), engine("second", "b", "c")])
+            .await;
+        start_runner(&state);
+
+        let job_id = submit(&state).await;
+        let job = wait_for_finish(&state, &job_id).await;
+
+        assert_eq!(job.status, JobStatus::Failed);
+        // 第一步完成後為 50，第二步上傳期間再推進，但不會到達完成的進度
+        assert!((50..90).contains(&job.progress), "{}", job.progress);
+        let message = job.error_message.unwrap();
+        assert!(message.contains("2/2"), "{}", message);
+        assert!(message.contains("second"), "{}", message);
+        assert_eq!(calls.lock().unwrap().len(), 2);
+    }
+
+    fn engines_with_schema() -> Vec<Engine> {
+        let mut first = engine("first", "a", "b");
+        first.params_schema = Some(json!({
+            "type": "object",
+            "properties": { "quality": { "type": "integer" } },
+            "additionalProperties": false
+        }));
+        vec![first, engine("second", "b", "c")]
+    }
+
+    async fn submit_with_options(state: &AppState, options: Value) -> axum_test::TestResponse {
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(b"data".to_vec()).file_name("doc.a"))
+            .add_text(
+                "params",
+                json!({ "output_format": "c", "options": options }).to_string(),
+            );
+        server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .multipart(form)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_options_sent_only_to_declaring_engine() {
+        let (backend_url, calls) = start_options_backend().await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        state
+            .engine_registry
+            .replace_engines(engines_with_schema())
+            .await;
+        start_runner(&state);
+
+        let response = submit_with_options(&state, json!({ "quality": 5 })).await;
+        response.assert_status_ok();
+        let body: Value = response.json();
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let job = wait_for_finish(&state, job_id).await;
+
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                ("first".to_string(), Some(json!({ "quality": 5 }))),
+                ("second".to_string(), None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undeclared_option_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(engines_with_schema())
+            .await;
+
+        let response = submit_with_options(&state, json!({ "quality": 5, "dpi": 300 })).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        assert_eq!(body["details"][0]["path"], "options.dpi");
+        assert_eq!(state.job_store.get_user_jobs("user-1").await.len(), 0);
+    }
+}
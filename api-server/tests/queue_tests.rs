@@ -0,0 +1,497 @@
+//! Job queue dispatch tests
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use convertx_api::{JobPriority, JobQueue, QueueConfig, QueuedJob};
+use tokio::sync::Semaphore;
+
+fn queued(job_id: &str, engine_id: &str) -> QueuedJob {
+    queued_for("user-1", job_id, engine_id)
+}
+
+fn queued_for(user_id: &str, job_id: &str, engine_id: &str) -> QueuedJob {
+    QueuedJob {
+        job_id: job_id.to_string(),
+        engine_ids: vec![engine_id.to_string()],
+        user_id: user_id.to_string(),
+        priority: JobPriority::Normal,
+    }
+}
+
+fn queued_with(priority: JobPriority, user_id: &str, job_id: &str) -> QueuedJob {
+    QueuedJob {
+        priority,
+        ..queued_for(user_id, job_id, "pandoc")
+    }
+}
+
+fn config(max_workers: usize) -> QueueConfig {
+    QueueConfig {
+        max_workers,
+        engine_limits: HashMap::new(),
+        max_jobs_per_user: 0,
+    }
+}
+
+/// Runner that blocks each job until a permit is released and records
+/// the peak number of jobs running at once.
+#[derive(Clone)]
+struct GatedRunner {
+    gate: Arc<Semaphore>,
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+    started: Arc<Mutex<Vec<String>>>,
+}
+
+impl GatedRunner {
+    fn new() -> Self {
+        Self {
+            gate: Arc::new(Semaphore::new(0)),
+            running: Arc::new(AtomicUsize::new(0)),
+            peak: Arc::new(AtomicUsize::new(0)),
+            started: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn start(&self, queue: &JobQueue) {
+        let runner = self.clone();
+        queue.start(move |job_id, _cancel| {
+            let runner = runner.clone();
+            async move {
+                runner.started.lock().unwrap().push(job_id);
+                let now = runner.running.fetch_add(1, Ordering::SeqCst) + 1;
+                runner.peak.fetch_max(now, Ordering::SeqCst);
+                runner.gate.acquire().await.unwrap().forget();
+                runner.running.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+    }
+
+    fn started(&self) -> Vec<String> {
+        self.started.lock().unwrap().clone()
+    }
+}
+
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+}
+
+mod queue_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_global_worker_limit() {
+        let queue = JobQueue::new(config(2));
+        let runner = GatedRunner::new();
+        runner.start(&queue);
+
+        for i in 0..5 {
+            queue
+                .enqueue(queued(&format!("job-{}", i), "dasel"))
+                .await
+                .unwrap();
+        }
+        settle().await;
+
+        assert_eq!(queue.running_count().await, 2);
+        assert_eq!(queue.pending_count().await, 3);
+
+        runner.gate.add_permits(5);
+        settle().await;
+
+        assert_eq!(queue.running_count().await, 0);
+        assert_eq!(queue.pending_count().await, 0);
+        assert_eq!(runner.peak.load(Ordering::SeqCst), 2);
+        assert_eq!(runner.started().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_engine_limit_lets_other_engines_through() {
+        let queue = JobQueue::new(QueueConfig {
+            engine_limits: HashMap::from([("ffmpeg".to_string(), 1)]),
+            ..config(4)
+        });
+        let runner = GatedRunner::new();
+        runner.start(&queue);
+
+        queue.enqueue(queued("video-1", "ffmpeg")).await.unwrap();
+        queue.enqueue(queued("video-2", "ffmpeg")).await.unwrap();
+        queue.enqueue(queued("data-1", "dasel")).await.unwrap();
+        settle().await;
+
+        let started = runner.started();
+        assert!(started.contains(&"video-1".to_string()));
+        assert!(started.contains(&"data-1".to_string()));
+        assert!(!started.contains(&"video-2".to_string()));
+        assert_eq!(queue.position("video-2").await, Some(1));
+
+        runner.gate.add_permits(1);
+        settle().await;
+        runner.gate.add_permits(2);
+        settle().await;
+
+        assert!(runner.started().contains(&"video-2".to_string()));
+        assert_eq!(queue.running_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_multi_step_job_reserves_every_engine() {
+        let queue = JobQueue::new(QueueConfig {
+            engine_limits: HashMap::from([("ffmpeg".to_string(), 1)]),
+            ..config(4)
+        });
+        let runner = GatedRunner::new();
+        runner.start(&queue);
+
+        queue.enqueue(queued("video-1", "ffmpeg")).await.unwrap();
+        queue
+            .enqueue(QueuedJob {
+                engine_ids: vec!["imagemagick".to_string(), "ffmpeg".to_string()],
+                ..queued("chain-1", "imagemagick")
+            })
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued("image-1", "imagemagick"))
+            .await
+            .unwrap();
+        settle().await;
+
+        assert_eq!(runner.started(), vec!["video-1", "image-1"]);
+        assert_eq!(queue.position("chain-1").await, Some(1));
+
+        runner.gate.add_permits(2);
+        settle().await;
+        assert_eq!(runner.started(), vec!["video-1", "image-1", "chain-1"]);
+
+        queue.enqueue(queued("video-2", "ffmpeg")).await.unwrap();
+        settle().await;
+        assert!(!runner.started().contains(&"video-2".to_string()));
+
+        runner.gate.add_permits(2);
+        settle().await;
+        assert!(runner.started().contains(&"video-2".to_string()));
+        assert_eq!(queue.running_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_queue_position() {
+        let queue = JobQueue::new(config(1));
+
+        queue.enqueue(queued("a", "pandoc")).await.unwrap();
+        queue.enqueue(queued("b", "pandoc")).await.unwrap();
+        queue.enqueue(queued("c", "pandoc")).await.unwrap();
+
+        assert_eq!(queue.position("a").await, Some(1));
+        assert_eq!(queue.position("c").await, Some(3));
+        assert_eq!(queue.position("missing").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_fifo_dispatch_order() {
+        let queue = JobQueue::new(config(1));
+        let runner = GatedRunner::new();
+
+        for id in ["first", "second", "third"] {
+            queue.enqueue(queued(id, "pandoc")).await.unwrap();
+        }
+        runner.start(&queue);
+        runner.gate.add_permits(3);
+        settle().await;
+
+        assert_eq!(runner.started(), vec!["first", "second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job_releases_slot() {
+        let queue = JobQueue::new(config(1));
+        let completed = Arc::new(AtomicUsize::new(0));
+        let counter = completed.clone();
+        queue.start(move |job_id, _cancel| {
+            let counter = counter.clone();
+            async move {
+                if job_id == "bad" {
+                    panic!("worker failure");
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        queue.enqueue(queued("bad", "pandoc")).await.unwrap();
+        queue.enqueue(queued("good", "pandoc")).await.unwrap();
+        settle().await;
+
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+        assert_eq!(queue.running_count().await, 0);
+    }
+}
+
+mod fairness_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_round_robin_across_users() {
+        let queue = JobQueue::new(config(1));
+        let runner = GatedRunner::new();
+
+        // user-a floods the queue before user-b submits anything
+        for i in 0..3 {
+            queue
+                .enqueue(queued_for("user-a", &format!("a-{}", i), "ffmpeg"))
+                .await
+                .unwrap();
+        }
+        queue
+            .enqueue(queued_for("user-b", "b-0", "ffmpeg"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_for("user-b", "b-1", "ffmpeg"))
+            .await
+            .unwrap();
+
+        assert_eq!(queue.position("b-0").await, Some(2));
+        assert_eq!(queue.position("a-1").await, Some(3));
+        assert_eq!(queue.position("a-2").await, Some(5));
+
+        runner.start(&queue);
+        runner.gate.add_permits(5);
+        settle().await;
+
+        assert_eq!(runner.started(), vec!["a-0", "b-0", "a-1", "b-1", "a-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_engine_does_not_stall_other_users() {
+        let queue = JobQueue::new(QueueConfig {
+            engine_limits: HashMap::from([("mineru".to_string(), 1)]),
+            ..config(4)
+        });
+        let runner = GatedRunner::new();
+        runner.start(&queue);
+
+        queue
+            .enqueue(queued_for("user-a", "a-0", "mineru"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_for("user-a", "a-1", "mineru"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_for("user-b", "b-0", "pandoc"))
+            .await
+            .unwrap();
+        settle().await;
+
+        let started = runner.started();
+        assert_eq!(started, vec!["a-0", "b-0"]);
+        runner.gate.add_permits(3);
+        settle().await;
+    }
+
+    #[tokio::test]
+    async fn test_user_quota() {
+        let queue = JobQueue::new(QueueConfig {
+            max_jobs_per_user: 2,
+            ..config(1)
+        });
+
+        queue
+            .enqueue(queued_for("user-a", "a-0", "pandoc"))
+            .await
+            .unwrap();
+        assert!(queue.check_user_quota("user-a").await.is_ok());
+        queue
+            .enqueue(queued_for("user-a", "a-1", "pandoc"))
+            .await
+            .unwrap();
+
+        let err = queue.check_user_quota("user-a").await.unwrap_err();
+        assert!(matches!(err, convertx_api::ApiError::TooManyJobs(2)));
+        assert!(queue.check_user_quota("user-b").await.is_ok());
+        assert_eq!(queue.user_in_flight("user-a").await, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_quota_enforced_on_parallel_enqueue() {
+        let queue = JobQueue::new(QueueConfig {
+            max_jobs_per_user: 3,
+            ..config(1)
+        });
+
+        let handles: Vec<_> = (0..10)
+            .map(|i| {
+                let queue = queue.clone();
+                tokio::spawn(async move {
+                    queue
+                        .enqueue(queued_for("user-a", &format!("a-{}", i), "pandoc"))
+                        .await
+                })
+            })
+            .collect();
+        let mut accepted = 0;
+        for handle in handles {
+            match handle.await.unwrap() {
+                Ok(()) => accepted += 1,
+                Err(e) => assert!(matches!(e, convertx_api::ApiError::TooManyJobs(3))),
+            }
+        }
+
+        assert_eq!(accepted, 3);
+        assert_eq!(queue.user_in_flight("user-a").await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_restored_jobs_bypass_quota() {
+        let queue = JobQueue::new(QueueConfig {
+            max_jobs_per_user: 1,
+            ..config(1)
+        });
+
+        queue.restore(queued_for("user-a", "a-0", "pandoc")).await;
+        queue.restore(queued_for("user-a", "a-1", "pandoc")).await;
+        assert_eq!(queue.user_in_flight("user-a").await, 2);
+        assert!(queue
+            .enqueue(queued_for("user-a", "a-2", "pandoc"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_running_jobs_count_towards_quota() {
+        let queue = JobQueue::new(QueueConfig {
+            max_jobs_per_user: 1,
+            ..config(1)
+        });
+        let runner = GatedRunner::new();
+        runner.start(&queue);
+
+        queue
+            .enqueue(queued_for("user-a", "a-0", "pandoc"))
+            .await
+            .unwrap();
+        settle().await;
+        assert_eq!(queue.running_count().await, 1);
+        assert!(queue.check_user_quota("user-a").await.is_err());
+
+        runner.gate.add_permits(1);
+        settle().await;
+        assert!(queue.check_user_quota("user-a").await.is_ok());
+    }
+
+    #[test]
+    fn test_too_many_jobs_status() {
+        use axum::response::IntoResponse;
+
+        let response = convertx_api::ApiError::TooManyJobs(5).into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+    }
+}
+
+mod priority_tests {
+    use super::*;
+    use convertx_api::JwtClaims;
+
+    fn claims(scope: &[&str]) -> JwtClaims {
+        JwtClaims {
+            sub: "user-1".to_string(),
+            email: None,
+            scope: scope.iter().map(|s| s.to_string()).collect(),
+            iat: 0,
+            exp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_high_priority_jumps_ahead() {
+        let queue = JobQueue::new(config(1));
+        let runner = GatedRunner::new();
+
+        queue
+            .enqueue(queued_with(JobPriority::Low, "batch", "nightly-0"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_with(JobPriority::Normal, "batch", "normal-0"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_with(JobPriority::High, "ui", "interactive-0"))
+            .await
+            .unwrap();
+
+        assert_eq!(queue.position("interactive-0").await, Some(1));
+        assert_eq!(queue.position("normal-0").await, Some(2));
+        assert_eq!(queue.position("nightly-0").await, Some(3));
+
+        runner.start(&queue);
+        runner.gate.add_permits(3);
+        settle().await;
+
+        assert_eq!(
+            runner.started(),
+            vec!["interactive-0", "normal-0", "nightly-0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_within_priority() {
+        let queue = JobQueue::new(config(1));
+
+        queue
+            .enqueue(queued_with(JobPriority::High, "user-a", "a-0"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_with(JobPriority::High, "user-a", "a-1"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_with(JobPriority::Low, "user-b", "b-low"))
+            .await
+            .unwrap();
+        queue
+            .enqueue(queued_with(JobPriority::High, "user-b", "b-0"))
+            .await
+            .unwrap();
+
+        assert_eq!(queue.position("a-0").await, Some(1));
+        assert_eq!(queue.position("b-0").await, Some(2));
+        assert_eq!(queue.position("a-1").await, Some(3));
+        assert_eq!(queue.position("b-low").await, Some(4));
+    }
+
+    #[test]
+    fn test_high_priority_requires_explicit_scope() {
+        assert!(!claims(&[]).can_use_high_priority());
+        assert!(!claims(&["convert"]).can_use_high_priority());
+        assert!(claims(&["convert", "priority:high"]).can_use_high_priority());
+        assert!(claims(&["*"]).can_use_high_priority());
+    }
+
+    #[test]
+    fn test_priority_params_parsing() {
+        let params: serde_json::Value = serde_json::json!({ "priority": "high" });
+        let priority: JobPriority = serde_json::from_value(params["priority"].clone()).unwrap();
+        assert_eq!(priority, JobPriority::High);
+        assert!(serde_json::from_value::<JobPriority>(serde_json::json!("urgent")).is_err());
+        assert!(JobPriority::High > JobPriority::Normal);
+        assert!(JobPriority::Normal > JobPriority::Low);
+    }
+}
+
+mod config_tests {
+    use convertx_api::config::parse_engine_limits;
+
+    #[test]
+    fn test_parse_engine_limits() {
+        let limits = parse_engine_limits("ffmpeg=2, MinerU=1,bad,zero=0,dasel=x");
+        assert_eq!(limits.get("ffmpeg"), Some(&2));
+        assert_eq!(limits.get("mineru"), Some(&1));
+        assert_eq!(limits.len(), 2);
+    }
+}
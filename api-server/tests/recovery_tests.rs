@@ -0,0 +1,131 @@
+//! Job recovery tests
+
+use convertx_api::{recovery, JobStatus};
+
+mod common;
+
+use common::{create_test_state, sample_job};
+
+mod recovery_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_processing_jobs_marked_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        state
+            .job_store
+            .update_status(&job.job_id, JobStatus::Processing)
+            .await;
+
+        let report = recovery::recover_jobs(&state).await;
+        assert_eq!(report.failed, vec![job.job_id.clone()]);
+        assert!(report.resumed.is_empty());
+
+        let job = state.job_store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(
+            job.error_message.as_deref(),
+            Some(recovery::INTERRUPTED_MESSAGE)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pending_job_without_upload_marked_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+
+        let report = recovery::recover_jobs(&state).await;
+        assert_eq!(report.failed, vec![job.job_id.clone()]);
+
+        let job = state.job_store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(
+            job.error_message.as_deref(),
+            Some(recovery::MISSING_UPLOAD_MESSAGE)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pending_job_with_upload_resumed() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let job = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        let upload_dir = dir.path().join("uploads").join(&job.job_id);
+        std::fs::create_dir_all(&upload_dir).unwrap();
+        std::fs::write(upload_dir.join("photo.png"), b"fake png").unwrap();
+
+        let report = recovery::recover_jobs(&state).await;
+        assert_eq!(report.resumed, vec![job.job_id.clone()]);
+        assert!(report.failed.is_empty());
+        assert_eq!(state.job_queue.position(&job.job_id).await, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_stale_staged_uploads_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let uploads = dir.path().join("uploads");
+        let job_dir = uploads.join("some-job");
+        std::fs::create_dir_all(&job_dir).unwrap();
+        std::fs::write(uploads.join(".upload-a1b2c3"), b"partial").unwrap();
+        std::fs::write(job_dir.join("photo.png"), b"fake png").unwrap();
+
+        recovery::recover_jobs(&state).await;
+        assert!(!uploads.join(".upload-a1b2c3").exists());
+        assert!(job_dir.join("photo.png").exists());
+    }
+
+    #[tokio::test]
+    async fn test_finished_jobs_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let completed = state
+            .job_store
+            .create_job(sample_job("user-1"))
+            .await
+            .unwrap();
+        state
+            .job_store
+            .complete_job(&completed.job_id, "/tmp/out.jpg".to_string())
+            .await;
+
+        let report = recovery::recover_jobs(&state).await;
+        assert!(report.resumed.is_empty());
+        assert!(report.failed.is_empty());
+
+        let job = state.job_store.get_job(&completed.job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_options_persisted_for_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let job = sample_job("user-1").with_options(Some(serde_json::json!({ "quality": 90 })));
+        let job = state.job_store.create_job(job).await.unwrap();
+
+        let stored = state.job_store.get_job(&job.job_id).await.unwrap();
+        assert_eq!(stored.options, Some(serde_json::json!({ "quality": 90 })));
+    }
+}
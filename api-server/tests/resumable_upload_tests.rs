@@ -0,0 +1,388 @@
+//! Resumable upload tests
+
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::http::{header, HeaderName, StatusCode};
+use axum_test::TestServer;
+use convertx_api::janitor::{self, RetentionPolicy};
+use convertx_api::resumable::{
+    is_valid_filename, parse_checksum, session_path, UploadSession, UPLOAD_CHECKSUM_HEADER,
+    UPLOAD_OFFSET_HEADER,
+};
+use convertx_api::{build_router, AppState, Engine};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+
+mod common;
+
+use common::{create_test_state, generate_token};
+
+fn bearer(user_id: &str) -> String {
+    format!("Bearer {}", generate_token(user_id))
+}
+
+/// An engine converting `txt` to `out`.
+fn engine() -> Engine {
+    Engine {
+        engine_name: "Tool".to_string(),
+        ..common::engine("tool", &["txt"], &["out"])
+    }
+}
+
+async fn create_server(dir: &TempDir) -> (AppState, TestServer) {
+    let state = create_test_state(dir);
+    state.engine_registry.replace_engines(vec![engine()]).await;
+    let server = TestServer::new(build_router(state.clone())).unwrap();
+    (state, server)
+}
+
+fn checksum(data: &[u8]) -> String {
+    let digest: String = Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256 {}", digest)
+}
+
+async fn create_upload(server: &TestServer, filename: &str, size: usize) -> (StatusCode, Value) {
+    let response = server
+        .post("/api/v1/uploads")
+        .add_header(header::AUTHORIZATION, bearer("user-1"))
+        .json(&json!({ "filename": filename, "size": size }))
+        .await;
+    (response.status_code(), response.json())
+}
+
+async fn patch_chunk(
+    server: &TestServer,
+    upload_id: &str,
+    offset: usize,
+    data: &[u8],
+    checksum_value: &str,
+) -> (StatusCode, Value) {
+    let response = server
+        .patch(&format!("/api/v1/uploads/{}", upload_id))
+        .add_header(header::AUTHORIZATION, bearer("user-1"))
+        .add_header(
+            HeaderName::from_static(UPLOAD_OFFSET_HEADER),
+            offset.to_string(),
+        )
+        .add_header(
+            HeaderName::from_static(UPLOAD_CHECKSUM_HEADER),
+            checksum_value.to_string(),
+        )
+        .bytes(Bytes::copy_from_slice(data))
+        .await;
+    (response.status_code(), response.json())
+}
+
+async fn complete(server: &TestServer, upload_id: &str, params: Value) -> (StatusCode, Value) {
+    let response = server
+        .post(&format!("/api/v1/uploads/{}/complete", upload_id))
+        .add_header(header::AUTHORIZATION, bearer("user-1"))
+        .json(&params)
+        .await;
+    (response.status_code(), response.json())
+}
+
+async fn current_offset(server: &TestServer, upload_id: &str) -> u64 {
+    let response = server
+        .get(&format!("/api/v1/uploads/{}", upload_id))
+        .add_header(header::AUTHORIZATION, bearer("user-1"))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    body["data"]["offset"].as_u64().unwrap()
+}
+
+mod protocol_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksum() {
+        let digest = "a".repeat(64);
+        assert_eq!(
+            parse_checksum(&format!("sha256 {}", digest)).unwrap(),
+            digest
+        );
+        assert_eq!(
+            parse_checksum(&format!("SHA256 {}", "AB".repeat(32))).unwrap(),
+            "ab".repeat(32)
+        );
+        assert!(parse_checksum(&format!("md5 {}", digest)).is_err());
+        assert!(parse_checksum("sha256 abc").is_err());
+        assert!(parse_checksum(&digest).is_err());
+    }
+
+    #[test]
+    fn test_filename_validation() {
+        assert!(is_valid_filename("clip.mp4"));
+        assert!(!is_valid_filename(""));
+        assert!(!is_valid_filename(".."));
+        assert!(!is_valid_filename("../clip.mp4"));
+        assert!(!is_valid_filename("dir\\clip.mp4"));
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_skip_active_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        let store = &state.upload_sessions;
+
+        let mut idle = UploadSession::new("user-1".into(), "a.txt".into(), 10, 0);
+        idle.expires_at -= 10;
+        let mut writing = UploadSession::new("user-1".into(), "b.txt".into(), 10, 0);
+        writing.expires_at -= 10;
+        let fresh = UploadSession::new("user-1".into(), "c.txt".into(), 10, 3600);
+        for session in [&idle, &writing, &fresh] {
+            store.create(session.clone()).await.unwrap();
+        }
+
+        let lock = store.lock(&writing.upload_id).unwrap();
+        assert!(store.lock(&writing.upload_id).is_none());
+
+        let now = chrono::Utc::now().timestamp();
+        let taken: Vec<String> = store
+            .take_expired(now)
+            .await
+            .into_iter()
+            .map(|s| s.upload_id)
+            .collect();
+        assert_eq!(taken, vec![idle.upload_id.clone()]);
+        assert!(store.get(&idle.upload_id).await.is_none());
+        assert!(store.get(&fresh.upload_id).await.is_some());
+
+        drop(lock);
+        assert_eq!(store.take_expired(now).await.len(), 1);
+    }
+}
+
+mod rest_upload_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chunked_upload_creates_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, server) = create_server(&dir).await;
+        let content: Vec<u8> = (0..10_000).map(|i| b'a' + (i % 26) as u8).collect();
+
+        let (status, body) = create_upload(&server, "notes.txt", content.len()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["offset"], 0);
+        assert_eq!(body["data"]["size"], content.len());
+        let upload_id = body["data"]["upload_id"].as_str().unwrap().to_string();
+
+        for chunk in content.chunks(4096) {
+            let offset = current_offset(&server, &upload_id).await as usize;
+            let (status, body) =
+                patch_chunk(&server, &upload_id, offset, chunk, &checksum(chunk)).await;
+            assert_eq!(status, StatusCode::OK, "{}", body);
+            assert_eq!(body["data"]["offset"], offset + chunk.len());
+        }
+
+        let (status, body) = complete(&server, &upload_id, json!({ "output_format": "out" })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["data"]["engine_id"], "tool");
+
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let job = state.job_store.get_job(job_id).await.unwrap();
+        assert_eq!(job.original_filename, "notes.txt");
+        let stored =
+            std::fs::read(dir.path().join("uploads").join(job_id).join("notes.txt")).unwrap();
+        assert_eq!(stored, content);
+
+        // 完成後工作階段即移除
+        let response = server
+            .get(&format!("/api/v1/uploads/{}", upload_id))
+            .add_header(header::AUTHORIZATION, bearer("user-1"))
+            .await;
+        response.assert_status(StatusCode::NOT_FOUND);
+        assert!(!session_path(&state.config.upload_dir, &upload_id).exists());
+    }
+
+    #[tokio::test]
+    async fn test_offset_mismatch_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_state, server) = create_server(&dir).await;
+        let (_, body) = create_upload(&server, "notes.txt", 10).await;
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+
+        let (status, body) =
+            patch_chunk(&server, upload_id, 5, b"fghij", &checksum(b"fghij")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "UPLOAD_CONFLICT");
+        assert_eq!(current_offset(&server, upload_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_discards_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, server) = create_server(&dir).await;
+        let (_, body) = create_upload(&server, "notes.txt", 10).await;
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+
+        patch_chunk(&server, upload_id, 0, b"abcde", &checksum(b"abcde")).await;
+        let (status, body) =
+            patch_chunk(&server, upload_id, 5, b"fgXij", &checksum(b"fghij")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "CHECKSUM_MISMATCH");
+        assert_eq!(current_offset(&server, upload_id).await, 5);
+        let path = session_path(&state.config.upload_dir, upload_id);
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcde");
+
+        let (status, body) =
+            patch_chunk(&server, upload_id, 5, b"fghij", &checksum(b"fghij")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["offset"], 10);
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcdefghij");
+    }
+
+    #[tokio::test]
+    async fn test_resume_discards_unconfirmed_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, server) = create_server(&dir).await;
+        let (_, body) = create_upload(&server, "notes.txt", 10).await;
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+        patch_chunk(&server, upload_id, 0, b"abcde", &checksum(b"abcde")).await;
+
+        // 模擬連線中斷時已寫入但未確認的內容
+        let path = session_path(&state.config.upload_dir, upload_id);
+        std::fs::write(&path, b"abcdeXX").unwrap();
+
+        let offset = current_offset(&server, upload_id).await;
+        assert_eq!(offset, 5);
+        patch_chunk(&server, upload_id, 5, b"fghij", &checksum(b"fghij")).await;
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcdefghij");
+    }
+
+    #[tokio::test]
+    async fn test_chunk_beyond_declared_size_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_state, server) = create_server(&dir).await;
+        let (_, body) = create_upload(&server, "notes.txt", 4).await;
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+
+        let (status, body) =
+            patch_chunk(&server, upload_id, 0, b"abcde", &checksum(b"abcde")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_INPUT");
+        assert_eq!(current_offset(&server, upload_id).await, 0);
+
+        let (status, _) = patch_chunk(&server, upload_id, 0, b"abc", &checksum(b"abc")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = complete(&server, upload_id, json!({ "output_format": "out" })).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["message"].as_str().unwrap().contains("3 of 4"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_completion_keeps_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_state, server) = create_server(&dir).await;
+        let (_, body) = create_upload(&server, "notes.txt", 5).await;
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+        patch_chunk(&server, upload_id, 0, b"hello", &checksum(b"hello")).await;
+
+        let (status, body) = complete(&server, upload_id, json!({ "output_format": "pdf" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "UNSUPPORTED_CONVERSION");
+        assert_eq!(current_offset(&server, upload_id).await, 5);
+
+        let (status, _) = complete(&server, upload_id, json!({ "output_format": "out" })).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_session_validation_and_ownership() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, server) = create_server(&dir).await;
+
+        let (status, body) = create_upload(&server, "notes.txt", 0).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_INPUT");
+        let (status, _) = create_upload(&server, "../notes.txt", 10).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let too_large = state.config.max_file_size as usize + 1;
+        let (status, body) = create_upload(&server, "clip.mp4", too_large).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["code"], "FILE_TOO_LARGE");
+
+        let (_, body) = create_upload(&server, "notes.txt", 10).await;
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+        let response = server
+            .get(&format!("/api/v1/uploads/{}", upload_id))
+            .add_header(header::AUTHORIZATION, bearer("user-2"))
+            .await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let response = server
+            .patch(&format!("/api/v1/uploads/{}", upload_id))
+            .add_header(header::AUTHORIZATION, bearer("user-1"))
+            .add_header(HeaderName::from_static(UPLOAD_OFFSET_HEADER), "0")
+            .bytes(Bytes::from_static(b"abc"))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        let response = server
+            .delete(&format!("/api/v1/uploads/{}", upload_id))
+            .add_header(header::AUTHORIZATION, bearer("user-1"))
+            .await;
+        response.assert_status_ok();
+        assert!(!session_path(&state.config.upload_dir, upload_id).exists());
+        assert!(state.upload_sessions.get(upload_id).await.is_none());
+    }
+}
+
+mod expiry_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_janitor_removes_expired_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+
+        let mut session = UploadSession::new("user-1".into(), "clip.mp4".into(), 100, 0);
+        session.expires_at -= 60;
+        let session = state.upload_sessions.create(session).await.unwrap();
+        let path = session_path(&state.config.upload_dir, &session.upload_id);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, [0u8; 40]).unwrap();
+
+        let policy = RetentionPolicy {
+            completed_ttl: Duration::from_secs(3600),
+            failed_ttl: Duration::from_secs(3600),
+            interval: Duration::ZERO,
+        };
+        let report = janitor::sweep(&state, &policy).await;
+        assert_eq!(report.expired_uploads, vec![session.upload_id.clone()]);
+        assert_eq!(report.reclaimed_bytes, 40);
+        assert!(!path.exists());
+        assert!(state
+            .upload_sessions
+            .get(&session.upload_id)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_session_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = create_test_state(&dir);
+        state.config.upload_session_ttl_hours = 0;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let (status, body) = create_upload(&server, "notes.txt", 10).await;
+        assert_eq!(status, StatusCode::OK);
+        let upload_id = body["data"]["upload_id"].as_str().unwrap();
+
+        let response = server
+            .get(&format!("/api/v1/uploads/{}", upload_id))
+            .add_header(header::AUTHORIZATION, bearer("user-1"))
+            .await;
+        response.assert_status(StatusCode::NOT_FOUND);
+        let body: Value = response.json();
+        assert_eq!(body["code"], "UPLOAD_NOT_FOUND");
+    }
+}
@@ -0,0 +1,326 @@
+//! Input format detection tests
+
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::Multipart;
+use axum::http::header;
+use axum::routing::post;
+use axum::Router;
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::sniff::{detect_input_format, sniff_format};
+use convertx_api::{build_router, Engine, Job, JobStatus};
+use serde_json::{json, Value};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+mod common;
+
+use common::{
+    create_test_state, create_test_state_with_backend, generate_token, serve_backend, start_runner,
+    wait_for_status,
+};
+
+const PDF: &[u8] = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n1 0 obj";
+const JPEG: &[u8] = b"\xFF\xD8\xFF\xE0\0\x10JFIF\0";
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+fn zip_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, content) in entries {
+        zip.start_file(*name, options).unwrap();
+        zip.write_all(content).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+fn sniffed(data: &[u8]) -> Option<&'static str> {
+    sniff_format(data).map(|s| s.format)
+}
+
+/// An engine converting `input` to `out`.
+fn engine(engine_id: &str, input: &str) -> Engine {
+    common::engine(engine_id, &[input], &["out"])
+}
+
+mod sniff_format_tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_signatures() {
+        assert_eq!(sniffed(PDF), Some("pdf"));
+        assert_eq!(sniffed(PNG), Some("png"));
+        assert_eq!(sniffed(JPEG), Some("jpg"));
+        assert_eq!(sniffed(b"GIF89a\x01\0"), Some("gif"));
+        assert_eq!(sniffed(b"RIFF\x24\0\0\0WEBPVP8 "), Some("webp"));
+        assert_eq!(sniffed(b"RIFF\x24\0\0\0WAVEfmt "), Some("wav"));
+        assert_eq!(sniffed(b"\0\0\0\x18ftypisom\0\0\x02\0"), Some("mp4"));
+        assert_eq!(sniffed(b"\0\0\0\x18ftypheic\0\0\0\0"), Some("heic"));
+        assert_eq!(
+            sniffed(b"\0\0\0\x1cftypmif1\0\0\0\0mif1avifmiaf"),
+            Some("avif")
+        );
+        assert_eq!(sniffed(b"\0\0\0\x18ftypmif1\0\0\0\0heicmif1"), Some("heic"));
+        assert_eq!(sniffed(b"\0\0\0\x14ftypmif1\0\0\0\0mif1"), None);
+        // 相容品牌只看到 box 結尾，之後的資料不算
+        assert_eq!(sniffed(b"\0\0\0\x14ftypmsf1\0\0\0\0msf1avif"), None);
+        assert_eq!(
+            sniffed(b"\x1a\x45\xdf\xa3\x9f\x42\x82\x84webm"),
+            Some("webm")
+        );
+        assert_eq!(sniffed(b"ID3\x04\0\0\0\0\0\0"), Some("mp3"));
+    }
+
+    #[test]
+    fn test_bmp_header_validated() {
+        let mut bmp = b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0".to_vec();
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        assert_eq!(sniffed(&bmp), Some("bmp"));
+        assert!(!sniff_format(&bmp).unwrap().weak);
+
+        assert_eq!(sniffed(b"BMW,320i,2019\nAudi,A4,2020\n"), None);
+        let mut reserved = bmp.clone();
+        reserved[7] = 1;
+        assert_eq!(sniffed(&reserved), None);
+    }
+
+    #[test]
+    fn test_short_signatures_are_weak() {
+        assert!(sniff_format(b"\xFF\x0A\xFA\x7F").unwrap().weak);
+        assert!(sniff_format(b"\xFF\xFB\x90\x64").unwrap().weak);
+        assert!(!sniff_format(b"ID3\x04\0\0\0\0\0\0").unwrap().weak);
+        assert!(!sniff_format(b"\0\0\0\x0cJXL \r\n\x87\n").unwrap().weak);
+    }
+
+    #[test]
+    fn test_zip_based_documents() {
+        let docx = zip_with(&[
+            ("[Content_Types].xml", b"<Types/>"),
+            ("word/document.xml", b"<w:document/>"),
+        ]);
+        assert_eq!(sniffed(&docx), Some("docx"));
+
+        let xlsx = zip_with(&[
+            ("[Content_Types].xml", b"<Types/>"),
+            ("xl/workbook.xml", b""),
+        ]);
+        assert_eq!(sniffed(&xlsx), Some("xlsx"));
+
+        let epub = zip_with(&[
+            ("mimetype", b"application/epub+zip"),
+            ("META-INF/container.xml", b""),
+        ]);
+        assert_eq!(sniffed(&epub), Some("epub"));
+
+        // 一般 ZIP 無法判斷內容
+        let plain = zip_with(&[("readme.txt", b"hello")]);
+        assert_eq!(sniffed(&plain), None);
+    }
+
+    #[test]
+    fn test_text_content() {
+        assert_eq!(
+            sniffed(b"\xEF\xBB\xBF  <?xml version=\"1.0\"?>\n<svg xmlns=\"\"/>"),
+            Some("svg")
+        );
+        assert_eq!(sniffed(b"# Title\n\nSome *markdown*"), None);
+        assert_eq!(sniffed(b"a,b,c\n1,2,3"), None);
+        assert_eq!(sniffed(b""), None);
+    }
+}
+
+mod detect_tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_extension_kept() {
+        let detected = detect_input_format("photo.JPEG", JPEG).unwrap();
+        assert_eq!(detected.format, "jpeg");
+        assert_eq!(detected.warning, None);
+
+        // 內容無法判斷時以副檔名為準
+        let detected = detect_input_format("notes.md", b"# hi").unwrap();
+        assert_eq!(detected.format, "md");
+        assert_eq!(detected.warning, None);
+    }
+
+    #[test]
+    fn test_missing_or_wrong_extension() {
+        let detected = detect_input_format("scan", PDF).unwrap();
+        assert_eq!(detected.format, "pdf");
+        assert!(detected.warning.is_some());
+
+        let detected = detect_input_format("photo.png", JPEG).unwrap();
+        assert_eq!(detected.format, "jpg");
+        assert!(detected.warning.unwrap().contains(".png"));
+
+        let detected = detect_input_format("photo.JPG.tmp", JPEG).unwrap();
+        assert_eq!(detected.format, "jpg");
+
+        assert_eq!(detect_input_format("scan", b"plain text"), None);
+    }
+
+    #[test]
+    fn test_weak_match_keeps_extension() {
+        let detected = detect_input_format("cars.csv", b"BMW,320i,2019\nAudi,A4,2020\n").unwrap();
+        assert_eq!(detected.format, "csv");
+        assert_eq!(detected.warning, None);
+
+        let detected = detect_input_format("data.bin", b"\xFF\xFB\x90\x64\0\0").unwrap();
+        assert_eq!(detected.format, "bin");
+        assert_eq!(detected.warning, None);
+
+        // 沒有副檔名時仍使用短特徵
+        let detected = detect_input_format("track", b"\xFF\xFB\x90\x64\0\0").unwrap();
+        assert_eq!(detected.format, "mp3");
+        assert!(detected.warning.is_some());
+    }
+
+    #[test]
+    fn test_upload_filename() {
+        let job = |name: &str, format: &str| {
+            Job::new(
+                "user-1".to_string(),
+                name.to_string(),
+                format.to_string(),
+                "out".to_string(),
+                "tool".to_string(),
+            )
+        };
+        assert_eq!(job("Photo.JPG", "jpg").upload_filename(), "Photo.JPG");
+        assert_eq!(job("photo.png", "jpg").upload_filename(), "photo.jpg");
+        assert_eq!(
+            job("photo.JPG.tmp", "jpg").upload_filename(),
+            "photo.JPG.jpg"
+        );
+        assert_eq!(job("scan", "pdf").upload_filename(), "scan.pdf");
+        // 原始檔名中的路徑不會帶入儲存路徑
+        assert_eq!(
+            job("../../etc/photo.jpg", "jpg").upload_filename(),
+            "photo.jpg"
+        );
+        assert_eq!(job("dir/scan", "pdf").upload_filename(), "scan.pdf");
+        assert_eq!(job("..", "pdf").upload_filename(), "upload.pdf");
+    }
+}
+
+mod rest_detect_tests {
+    use super::*;
+
+    async fn upload(server: &TestServer, filename: &str, data: &[u8]) -> Value {
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(data.to_vec()).file_name(filename))
+            .add_text("params", json!({ "output_format": "out" }).to_string());
+        let response = server
+            .post("/api/v1/convert")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .multipart(form)
+            .await;
+        response.json()
+    }
+
+    #[tokio::test]
+    async fn test_content_overrides_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("pdftool", "pdf"), engine("jpgtool", "jpg")])
+            .await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let body = upload(&server, "scan", PDF).await;
+        assert_eq!(body["data"]["engine_id"], "pdftool");
+        assert_eq!(body["data"]["warnings"].as_array().unwrap().len(), 1);
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let job = state.job_store.get_job(job_id).await.unwrap();
+        assert_eq!(job.original_filename, "scan");
+        assert_eq!(job.input_format, "pdf");
+        assert_eq!(job.warnings.len(), 1);
+        assert!(dir
+            .path()
+            .join("uploads")
+            .join(job_id)
+            .join("scan.pdf")
+            .exists());
+
+        let body = upload(&server, "photo.png", JPEG).await;
+        assert_eq!(body["data"]["engine_id"], "jpgtool");
+
+        let response = server
+            .get(&format!(
+                "/api/v1/jobs/{}",
+                body["data"]["job_id"].as_str().unwrap()
+            ))
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+        let status: Value = response.json();
+        assert_eq!(status["data"]["input_format"], "jpg");
+        assert!(status["data"]["warnings"][0]
+            .as_str()
+            .unwrap()
+            .contains(".png"));
+    }
+
+    #[tokio::test]
+    async fn test_matching_upload_has_no_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("pdftool", "pdf")])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let body = upload(&server, "paper.pdf", PDF).await;
+        assert_eq!(body["data"]["warnings"], json!([]));
+
+        let body = upload(&server, "scan", b"plain text").await;
+        assert_eq!(body["code"], "INVALID_INPUT");
+    }
+
+    #[tokio::test]
+    async fn test_backend_receives_corrected_filename() {
+        let filenames = Arc::new(Mutex::new(Vec::new()));
+        let recorder = filenames.clone();
+        let app = Router::new().route(
+            "/api/convert",
+            post(move |mut multipart: Multipart| {
+                let recorder = recorder.clone();
+                async move {
+                    while let Some(field) = multipart.next_field().await.unwrap() {
+                        if field.name() == Some("file") {
+                            let name = field.file_name().unwrap_or_default().to_string();
+                            recorder.lock().unwrap().push(name);
+                        }
+                    }
+                    "converted"
+                }
+            }),
+        );
+        let backend_url = serve_backend(app).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend_url);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("jpgtool", "jpg")])
+            .await;
+        start_runner(&state);
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let body = upload(&server, "photo.png", JPEG).await;
+        let job_id = body["data"]["job_id"].as_str().unwrap().to_string();
+
+        wait_for_status(&state, &job_id, JobStatus::Completed).await;
+        assert_eq!(*filenames.lock().unwrap(), vec!["photo.jpg".to_string()]);
+    }
+}
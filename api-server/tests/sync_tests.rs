@@ -0,0 +1,271 @@
+//! Backend engine sync tests
+
+use axum::http::header;
+use axum::routing::get;
+use axum::{Json, Router};
+use axum_test::TestServer;
+use convertx_api::engine::{default_engines, EngineCapability};
+use convertx_api::sync::{merge_engines, sync_engines, BackendConverter};
+use convertx_api::{build_router, Engine, EngineRegistry};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{create_test_state_with_backend, generate_token, serve_backend};
+
+/// Start a backend stub serving whatever listing is currently in `listing`.
+async fn start_backend(listing: Arc<Mutex<Value>>) -> String {
+    let app = Router::new().route(
+        "/api/converters",
+        get(move || {
+            let listing = listing.clone();
+            async move { Json(listing.lock().unwrap().clone()) }
+        }),
+    );
+    serve_backend(app).await
+}
+
+fn listing(converters: Value) -> Arc<Mutex<Value>> {
+    Arc::new(Mutex::new(json!({
+        "success": true,
+        "converters": converters,
+        "platform": "linux/x64",
+    })))
+}
+
+fn converter(name: &str, inputs: &[&str], outputs: &[&str], available: bool) -> BackendConverter {
+    BackendConverter {
+        name: name.to_string(),
+        inputs: inputs.iter().map(|s| s.to_string()).collect(),
+        outputs: outputs.iter().map(|s| s.to_string()).collect(),
+        available,
+    }
+}
+
+fn local_engine(engine_id: &str, capabilities: Vec<EngineCapability>) -> Engine {
+    Engine {
+        engine_name: engine_id.to_uppercase(),
+        description: "local description".to_string(),
+        capabilities,
+        max_file_size_mb: 42,
+        ..common::engine(engine_id, &["a", "b"], &["x", "y"])
+    }
+}
+
+mod merge_tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_formats_override_local_lists() {
+        let catalog = vec![local_engine("tool", Vec::new())];
+        let merged = merge_engines(
+            &catalog,
+            &[converter("Tool", &[".A", "c", "c"], &["z"], true)],
+        );
+
+        let engine = &merged[0];
+        assert_eq!(engine.input_formats, vec!["a", "c"]);
+        assert_eq!(engine.output_formats, vec!["z"]);
+        assert_eq!(engine.description, "local description");
+        assert_eq!(engine.max_file_size_mb, 42);
+        assert!(engine.is_usable());
+    }
+
+    #[test]
+    fn test_unadvertised_and_unavailable_engines() {
+        let catalog = vec![
+            local_engine("gone", Vec::new()),
+            local_engine("off", Vec::new()),
+        ];
+        let merged = merge_engines(&catalog, &[converter("off", &["a"], &["x"], false)]);
+
+        assert!(merged.iter().all(|e| !e.available && e.enabled));
+        assert!(!merged[0].supports_conversion("a", "x"));
+    }
+
+    #[test]
+    fn test_backend_only_engine_is_added() {
+        let merged = merge_engines(&[], &[converter("NewTool", &["q"], &["r"], true)]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].engine_id, "newtool");
+        assert_eq!(merged[0].engine_name, "NewTool");
+        assert!(merged[0].supports_conversion("q", "r"));
+    }
+
+    #[test]
+    fn test_converter_names_normalized() {
+        let catalog = vec![
+            local_engine("pdfpackager", Vec::new()),
+            local_engine("mineru", Vec::new()),
+        ];
+        let merged = merge_engines(
+            &catalog,
+            &[
+                converter("PDF Packager", &["pdf"], &["tar"], true),
+                converter("MinerU", &["pdf"], &["md"], true),
+                converter("Fancy Tool-2", &["a"], &["b"], true),
+                converter("  ", &["a"], &["b"], true),
+            ],
+        );
+
+        let ids: Vec<&str> = merged.iter().map(|e| e.engine_id.as_str()).collect();
+        assert_eq!(ids, vec!["pdfpackager", "mineru", "fancytool2"]);
+        assert!(merged[0].available);
+        assert!(merged[0].supports_conversion("pdf", "tar"));
+        assert!(merged[1].supports_conversion("pdf", "md"));
+        assert_eq!(merged[2].engine_name, "Fancy Tool-2");
+    }
+
+    #[test]
+    fn test_capabilities_restricted_to_advertised_formats() {
+        let catalog = vec![local_engine(
+            "tool",
+            vec![
+                EngineCapability {
+                    input_format: "a".to_string(),
+                    output_formats: vec!["x".to_string(), "y".to_string()],
+                },
+                EngineCapability {
+                    input_format: "b".to_string(),
+                    output_formats: vec!["x".to_string()],
+                },
+            ],
+        )];
+
+        let merged = merge_engines(&catalog, &[converter("tool", &["a"], &["y"], true)]);
+        assert!(merged[0].supports_conversion("a", "y"));
+        assert!(!merged[0].supports_conversion("a", "x"));
+        assert!(!merged[0].supports_conversion("b", "x"));
+
+        // 沒有任何限定組合仍被支援時，不可退回為全部組合
+        let merged = merge_engines(&catalog, &[converter("tool", &["b"], &["y"], true)]);
+        assert!(!merged[0].available);
+        assert!(!merged[0].supports_conversion("b", "y"));
+    }
+}
+
+mod backend_sync_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync_applies_backend_listing() {
+        let backend = start_backend(listing(json!([
+            { "name": "ffmpeg", "inputs": ["mp4", "mkv"], "outputs": ["webm"], "available": true },
+            { "name": "MinerU", "inputs": ["pdf"], "outputs": ["md-t"], "available": false },
+            { "name": "newtool", "inputs": ["foo"], "outputs": ["bar"], "available": true },
+        ])))
+        .await;
+        let registry = EngineRegistry::new();
+        let client = reqwest::Client::new();
+
+        let diff = sync_engines(&registry, &client, &backend).await.unwrap();
+        assert_eq!(diff.added, vec!["newtool"]);
+        assert!(diff.removed.is_empty());
+
+        let ffmpeg = registry.get_engine("ffmpeg").await.unwrap();
+        assert_eq!(ffmpeg.input_formats, vec!["mp4", "mkv"]);
+        assert!(ffmpeg.supports_conversion("mkv", "webm"));
+        assert!(!ffmpeg.supports_conversion("mp4", "mp3"));
+
+        assert!(!registry.is_engine_available("mineru").await);
+        assert!(!registry.is_engine_available("pandoc").await);
+        assert!(registry.is_engine_available("newtool").await);
+
+        let change = diff
+            .changed
+            .iter()
+            .find(|c| c.engine_id == "pandoc")
+            .unwrap();
+        assert_eq!(change.available, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_engine_dropped_by_backend_becomes_unavailable() {
+        let state = listing(json!([
+            { "name": "ffmpeg", "inputs": ["mp4"], "outputs": ["webm"] },
+            { "name": "vips", "inputs": ["png"], "outputs": ["webp"] },
+        ]));
+        let backend = start_backend(state.clone()).await;
+        let registry = EngineRegistry::new();
+        let client = reqwest::Client::new();
+
+        sync_engines(&registry, &client, &backend).await.unwrap();
+        assert!(registry.is_engine_available("ffmpeg").await);
+
+        state.lock().unwrap()["converters"] = json!([
+            { "name": "vips", "inputs": ["png"], "outputs": ["webp"] },
+        ]);
+        let diff = sync_engines(&registry, &client, &backend).await.unwrap();
+
+        assert!(!registry.is_engine_available("ffmpeg").await);
+        assert!(registry.get_engine("ffmpeg").await.is_some());
+        assert!(registry
+            .find_engine_for_conversion("mp4", "webm", 0)
+            .await
+            .is_none());
+        assert!(diff.changed.iter().any(|c| c.engine_id == "ffmpeg"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_sync_keeps_current_engines() {
+        let registry = EngineRegistry::new();
+        let client = reqwest::Client::new();
+
+        assert!(sync_engines(&registry, &client, "http://127.0.0.1:1")
+            .await
+            .is_err());
+
+        let backend = start_backend(listing(json!([]))).await;
+        assert!(sync_engines(&registry, &client, &backend).await.is_err());
+
+        assert!(registry.is_engine_available("pandoc").await);
+        assert_eq!(registry.list_engines().await.len(), default_engines().len());
+    }
+
+    #[tokio::test]
+    async fn test_catalog_reload_keeps_backend_listing() {
+        let backend = start_backend(listing(json!([
+            { "name": "ffmpeg", "inputs": ["mp4"], "outputs": ["webm"] },
+        ])))
+        .await;
+        let registry = EngineRegistry::new();
+        sync_engines(&registry, &reqwest::Client::new(), &backend)
+            .await
+            .unwrap();
+
+        registry.replace_engines(default_engines()).await;
+
+        let ffmpeg = registry.get_engine("ffmpeg").await.unwrap();
+        assert_eq!(ffmpeg.input_formats, vec!["mp4"]);
+        assert!(!registry.is_engine_available("pandoc").await);
+    }
+
+    #[tokio::test]
+    async fn test_engine_listing_reports_availability() {
+        let backend = start_backend(listing(json!([
+            { "name": "ffmpeg", "inputs": ["mp4"], "outputs": ["webm"] },
+        ])))
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with_backend(&dir, &backend);
+        sync_engines(&state.engine_registry, &reqwest::Client::new(), &backend)
+            .await
+            .unwrap();
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let response = server
+            .get("/api/v1/engines/pandoc")
+            .add_header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", generate_token("user-1")),
+            )
+            .await;
+
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["data"]["engine"]["enabled"], true);
+        assert_eq!(body["data"]["engine"]["available"], false);
+    }
+}
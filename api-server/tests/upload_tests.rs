@@ -0,0 +1,214 @@
+//! Streaming upload tests
+
+use axum::http::{header, StatusCode};
+use axum_test::multipart::{MultipartForm, Part};
+use axum_test::TestServer;
+use convertx_api::{build_router, Engine};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+mod common;
+
+use common::{create_test_state, create_test_state_with, generate_token};
+
+const MB: usize = 1024 * 1024;
+
+/// An engine converting `txt` and `pdf` to `out` with the given limit.
+fn engine(engine_id: &str, max_file_size_mb: u64) -> Engine {
+    Engine {
+        max_file_size_mb,
+        ..common::engine(engine_id, &["txt", "pdf"], &["out"])
+    }
+}
+
+/// Entries directly under the upload directory.
+fn upload_entries(dir: &TempDir) -> Vec<String> {
+    let mut entries: Vec<String> = std::fs::read_dir(dir.path().join("uploads"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    entries.sort();
+    entries
+}
+
+async fn post_form(server: &TestServer, form: MultipartForm) -> (StatusCode, Value) {
+    let response = server
+        .post("/api/v1/convert")
+        .add_header(
+            header::AUTHORIZATION,
+            format!("Bearer {}", generate_token("user-1")),
+        )
+        .multipart(form)
+        .await;
+    (response.status_code(), response.json())
+}
+
+mod streaming_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_upload_moved_into_job_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("tool", 10)])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let content: Vec<u8> = (0..3 * MB).map(|i| b'a' + (i % 26) as u8).collect();
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(content.clone()).file_name("notes.txt"))
+            .add_text("params", json!({ "output_format": "out" }).to_string());
+        let (status, body) = post_form(&server, form).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        assert_eq!(upload_entries(&dir), vec![job_id.to_string()]);
+        let stored_path = dir.path().join("uploads").join(job_id).join("notes.txt");
+        let stored = std::fs::read(stored_path).unwrap();
+        assert_eq!(stored, content);
+    }
+
+    #[tokio::test]
+    async fn test_format_detected_from_streamed_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("tool", 10)])
+            .await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let mut content = b"%PDF-1.7\n".to_vec();
+        content.resize(2 * MB, b' ');
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(content).file_name("scan"))
+            .add_text("params", json!({ "output_format": "out" }).to_string());
+        let (status, body) = post_form(&server, form).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let job_id = body["data"]["job_id"].as_str().unwrap();
+        let job = state.job_store.get_job(job_id).await.unwrap();
+        assert_eq!(job.input_format, "pdf");
+        assert!(dir
+            .path()
+            .join("uploads")
+            .join(job_id)
+            .join("scan.pdf")
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn test_engine_limit_applied_while_streaming() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("small", 1)])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        // 參數在檔案之前時，寫入期間即套用指定引擎的上限
+        let form = MultipartForm::new()
+            .add_text(
+                "params",
+                json!({ "output_format": "out", "engine_id": "small" }).to_string(),
+            )
+            .add_part(
+                "file",
+                Part::bytes(vec![b'a'; 2 * MB]).file_name("notes.txt"),
+            );
+        let (status, body) = post_form(&server, form).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body["message"].as_str().unwrap().contains(&MB.to_string()));
+        assert!(upload_entries(&dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_parallel_uploads_respect_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state_with(&dir, |config| config.max_jobs_per_user = 2);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("tool", 10)])
+            .await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        let requests = (0..6).map(|_| {
+            let form = MultipartForm::new()
+                .add_part("file", Part::bytes(vec![b'a'; MB]).file_name("notes.txt"))
+                .add_text("params", json!({ "output_format": "out" }).to_string());
+            post_form(&server, form)
+        });
+        let results = futures::future::join_all(requests).await;
+
+        let accepted = results.iter().filter(|(s, _)| *s == StatusCode::OK).count();
+        let rejected: Vec<_> = results
+            .iter()
+            .filter(|(s, _)| *s != StatusCode::OK)
+            .collect();
+        assert_eq!(accepted, 2);
+        assert!(rejected.iter().all(
+            |(s, body)| *s == StatusCode::TOO_MANY_REQUESTS && body["code"] == "TOO_MANY_JOBS"
+        ));
+
+        // 被拒絕的任務不留下記錄與檔案
+        assert_eq!(state.job_store.get_user_jobs("user-1").await.len(), 2);
+        assert_eq!(upload_entries(&dir).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_job_leaves_no_staged_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("tool", 10)])
+            .await;
+        let server = TestServer::new(build_router(state)).unwrap();
+
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(vec![b'a'; MB]).file_name("notes.txt"))
+            .add_text("params", json!({ "output_format": "pdf" }).to_string());
+        let (status, _) = post_form(&server, form).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let form = MultipartForm::new()
+            .add_part("file", Part::bytes(vec![b'a'; MB]).file_name("notes.txt"));
+        let (status, _) = post_form(&server, form).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert!(upload_entries(&dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_path_in_filename_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_state(&dir);
+        state
+            .engine_registry
+            .replace_engines(vec![engine("tool", 10)])
+            .await;
+        let server = TestServer::new(build_router(state.clone())).unwrap();
+
+        for filename in [
+            "../../other/notes.txt",
+            "nested/notes.txt",
+            "..\\notes.txt",
+            "..",
+        ] {
+            let form = MultipartForm::new()
+                .add_part("file", Part::bytes(b"text".to_vec()).file_name(filename))
+                .add_text("params", json!({ "output_format": "out" }).to_string());
+            let (status, body) = post_form(&server, form).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", filename);
+            assert_eq!(body["code"], "INVALID_INPUT");
+        }
+
+        // 在讀取內容前即拒絕，不會寫入任何檔案
+        assert!(!dir.path().join("other").exists());
+        assert!(!dir.path().join("uploads").exists());
+        assert!(state.job_store.get_user_jobs("user-1").await.is_empty());
+    }
+}